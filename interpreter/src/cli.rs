@@ -0,0 +1,264 @@
+/// Command-line options for the interpreter binary.
+///
+/// There are only a few, so we parse them by hand.
+///
+pub const USAGE: &str = "\
+Usage: interpreter [options] [command] [-- script args...]
+
+Commands:
+  run <file>     Run a script
+  tokens <file>  Print the tokens scanned from a script
+  ast <file>     Print the syntax tree parsed from a script
+  check <file>   Report errors in a script without running it
+  explain <code> Explain an error code, like E0001
+  <file>         Same as `run <file>`
+
+With no command, starts an interactive prompt. A file
+of `-` reads the script from stdin.
+
+Options:
+  -v, --verbose                 Print debugging output to stderr
+  --error-format=<human|json>   How to print errors; json prints
+                                one object per line to stderr
+  -h, --help                    Print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Repl,
+    Run(String),
+    Tokens(String),
+    Ast(String),
+    Check(String),
+    // Holds the error code to explain.
+    Explain(String),
+    Help,
+}
+
+impl Command {
+    /// The script file the command works on, if any.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Command::Run(path)
+            | Command::Tokens(path)
+            | Command::Ast(path)
+            | Command::Check(path) => Some(path),
+
+            Command::Repl | Command::Explain(_) | Command::Help => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    fn parse(value: &str) -> Result<ErrorFormat, String> {
+        match value {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+
+            _ => Err(format!("Unknown error format '{value}'.")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub verbose: bool,
+    pub error_format: ErrorFormat,
+    // Everything after `--`, passed through to the script.
+    pub script_args: Vec<String>,
+}
+
+impl Options {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut verbose = false;
+        let mut error_format = ErrorFormat::Human;
+        let mut help = false;
+        let mut positional = vec![];
+        let mut script_args = vec![];
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => {
+                    script_args = args.by_ref().cloned().collect();
+                    break;
+                }
+
+                "-v" | "--verbose" => verbose = true,
+                "-h" | "--help" => help = true,
+
+                "--error-format" => {
+                    let Some(value) = args.next() else {
+                        return Err(String::from("The '--error-format' option expects a value."));
+                    };
+                    error_format = ErrorFormat::parse(value)?;
+                }
+                option if option.starts_with("--error-format=") => {
+                    error_format = ErrorFormat::parse(&option["--error-format=".len()..])?;
+                }
+
+                option if option.starts_with('-') && option.len() > 1 => {
+                    return Err(format!("Unknown option '{option}'."))
+                }
+
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let command = if help {
+            Command::Help
+        } else {
+            Self::command(positional)?
+        };
+
+        Ok(Options {
+            command,
+            verbose,
+            error_format,
+            script_args,
+        })
+    }
+
+    fn command(positional: Vec<String>) -> Result<Command, String> {
+        let mut positional = positional.into_iter();
+
+        let Some(first) = positional.next() else {
+            return Ok(Command::Repl);
+        };
+
+        let make_command: fn(String) -> Command = match first.as_str() {
+            "run" => Command::Run,
+            "tokens" => Command::Tokens,
+            "ast" => Command::Ast,
+            "check" => Command::Check,
+            "explain" => Command::Explain,
+
+            // A bare file name runs it, like jlox.
+            _ => {
+                return match positional.next() {
+                    None => Ok(Command::Run(first)),
+                    Some(extra) => Err(format!("Unexpected argument '{extra}'.")),
+                }
+            }
+        };
+
+        let Some(argument) = positional.next() else {
+            let expected = if first == "explain" {
+                "an error code"
+            } else {
+                "a file"
+            };
+            return Err(format!("The '{first}' command expects {expected}."));
+        };
+        if let Some(extra) = positional.next() {
+            return Err(format!("Unexpected argument '{extra}'."));
+        }
+
+        Ok(make_command(argument))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Options::parse(&args)
+    }
+
+    fn command(args: &[&str]) -> Result<Command, String> {
+        parse(args).map(|options| options.command)
+    }
+
+    #[test]
+    fn passes_arguments_after_double_dash_to_the_script() {
+        let options = parse(&["run", "a.lox", "--", "-v", "--", "b.lox"]).unwrap();
+
+        assert_eq!(options.command, Command::Run(String::from("a.lox")));
+        assert_eq!(options.script_args, ["-v", "--", "b.lox"]);
+        assert!(!options.verbose);
+    }
+
+    #[test]
+    fn takes_the_error_format_in_either_form() {
+        for args in [
+            &["--error-format", "json", "check", "a.lox"][..],
+            &["check", "a.lox", "--error-format=json"][..],
+        ] {
+            let options = parse(args).unwrap();
+            assert_eq!(options.error_format, ErrorFormat::Json);
+            assert_eq!(options.command, Command::Check(String::from("a.lox")));
+        }
+
+        assert_eq!(parse(&["a.lox"]).unwrap().error_format, ErrorFormat::Human);
+    }
+
+    #[test]
+    fn rejects_unknown_or_missing_error_formats() {
+        assert_eq!(
+            command(&["--error-format", "xml", "a.lox"]),
+            Err(String::from("Unknown error format 'xml'."))
+        );
+        assert_eq!(
+            command(&["--error-format=", "a.lox"]),
+            Err(String::from("Unknown error format ''."))
+        );
+        assert_eq!(
+            command(&["a.lox", "--error-format"]),
+            Err(String::from("The '--error-format' option expects a value."))
+        );
+    }
+
+    #[test]
+    fn runs_a_bare_file() {
+        assert_eq!(command(&["a.lox"]), Ok(Command::Run(String::from("a.lox"))));
+        assert_eq!(command(&[]), Ok(Command::Repl));
+    }
+
+    #[test]
+    fn reads_stdin_for_a_dash() {
+        assert_eq!(command(&["-"]), Ok(Command::Run(String::from("-"))));
+        assert_eq!(
+            command(&["tokens", "-"]),
+            Ok(Command::Tokens(String::from("-")))
+        );
+    }
+
+    #[test]
+    fn rejects_missing_and_extra_arguments() {
+        assert_eq!(
+            command(&["explain"]),
+            Err(String::from("The 'explain' command expects an error code."))
+        );
+        assert_eq!(
+            command(&["ast"]),
+            Err(String::from("The 'ast' command expects a file."))
+        );
+        assert_eq!(
+            command(&["run", "a.lox", "b.lox"]),
+            Err(String::from("Unexpected argument 'b.lox'."))
+        );
+        assert_eq!(
+            command(&["a.lox", "b.lox"]),
+            Err(String::from("Unexpected argument 'b.lox'."))
+        );
+        assert_eq!(
+            command(&["--frobnicate", "a.lox"]),
+            Err(String::from("Unknown option '--frobnicate'."))
+        );
+    }
+
+    #[test]
+    fn prefers_help_to_any_command() {
+        assert_eq!(command(&["run", "a.lox", "-h"]), Ok(Command::Help));
+        assert_eq!(command(&["--help", "explain"]), Ok(Command::Help));
+    }
+}
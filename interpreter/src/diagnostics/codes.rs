@@ -0,0 +1,645 @@
+/// Stable codes for every error we report, with long-form
+/// explanations for `interpreter explain`, like `rustc --explain`.
+///
+/// Codes are never renumbered or reused. New ones go at the end.
+///
+use crate::interpreter::{ResolveErrorKind, RuntimeErrorKind};
+use crate::parser::{ParseErrorKind, ScanErrorKind};
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    // Ends with a newline.
+    pub explanation: &'static str,
+}
+
+impl ErrorCode {
+    /// Finds a code in the catalogue, ignoring case.
+    pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+        ERROR_CODES
+            .iter()
+            .find(|error_code| error_code.code.eq_ignore_ascii_case(code))
+    }
+}
+
+// ---------------------------
+// Codes for each kind of error.
+
+impl ScanErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScanErrorKind::UnterminatedString => "E0001",
+            ScanErrorKind::InvalidNumber => "E0002",
+            ScanErrorKind::UnexpectedCharacter => "E0003",
+            ScanErrorKind::InvalidUtf8 => "E0004",
+            ScanErrorKind::Io => "E0005",
+            ScanErrorKind::UnknownEscape => "E0026",
+            ScanErrorKind::InvalidUnicodeEscape => "E0027",
+            ScanErrorKind::InvalidCodePoint => "E0028",
+            ScanErrorKind::NumberOverflow => "E0029",
+            ScanErrorKind::UnterminatedComment => "E0032",
+        }
+    }
+}
+
+impl ParseErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseErrorKind::ExpectedExpression => "E0006",
+            ParseErrorKind::ExpectedToken => "E0007",
+            ParseErrorKind::InvalidAssignmentTarget => "E0008",
+            ParseErrorKind::TooManyArguments => "E0009",
+            ParseErrorKind::TooDeeplyNested => "E0033",
+        }
+    }
+}
+
+impl ResolveErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResolveErrorKind::AlreadyDeclared => "E0010",
+            ResolveErrorKind::ReadInOwnInitializer => "E0011",
+            ResolveErrorKind::ReturnOutsideFunction => "E0012",
+            ResolveErrorKind::ReturnValueFromInitializer => "E0013",
+            ResolveErrorKind::ThisOutsideClass => "E0014",
+            ResolveErrorKind::SuperOutsideClass => "E0015",
+            ResolveErrorKind::SuperWithoutSuperclass => "E0016",
+            ResolveErrorKind::InheritsFromItself => "E0017",
+        }
+    }
+}
+
+impl RuntimeErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeErrorKind::TypeMismatch => "E0018",
+            RuntimeErrorKind::UndefinedVariable => "E0019",
+            RuntimeErrorKind::NotCallable => "E0020",
+            RuntimeErrorKind::ArityMismatch => "E0021",
+            RuntimeErrorKind::StackOverflow => "E0022",
+            RuntimeErrorKind::NotAnInstance => "E0023",
+            RuntimeErrorKind::UndefinedProperty => "E0024",
+            RuntimeErrorKind::InvalidSuperclass => "E0025",
+            RuntimeErrorKind::DivisionByZero => "E0031",
+        }
+    }
+}
+
+// --------------
+// The catalogue.
+
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E0001",
+        title: "Unterminated string literal",
+        explanation: r#"A string literal was started with `"`, but the source ended before
+the closing `"`.
+
+Erroneous code example:
+
+    print "Hello, world!;
+
+Strings may span several lines, so a missing quote is often only
+noticed at the end of the file. Add the closing quote:
+
+    print "Hello, world!";
+"#,
+    },
+    ErrorCode {
+        code: "E0002",
+        title: "Invalid numeric literal",
+        explanation: r#"A number literal isn't in a format we understand.
+
+Erroneous code examples:
+
+    var a = 5.;
+    var b = 42..0;
+    var c = 7up;
+    var d = 0x;
+    var e = 1__000;
+    var f = 1e;
+    var g = 1e3d;
+
+A number is decimal digits, optionally followed by a `.` and more
+digits, which make an exact decimal. Then there may be an exponent
+like `e9` or `E-3`, which makes a float, or a `d`, which makes an
+exact decimal of a whole number. Integers can also be written in hex,
+binary or octal, after a `0x`, `0b` or `0o` prefix. A single `_` may
+separate any two digits. The literal can't be followed directly by
+a letter, digit, `_` or `.`:
+
+    var a = 5.0;
+    var b = 42.0;
+    var c = 0x1F + 0b1010 + 0o17;
+    var d = 1_000_000;
+    var e = 6.02E23;
+    var f = 1000d;
+"#,
+    },
+    ErrorCode {
+        code: "E0003",
+        title: "Unexpected character",
+        explanation: r#"The source contains a character that can't start any token.
+
+Erroneous code example:
+
+    var total = 10 # 2;
+
+Only ASCII letters, digits and `_` may appear in identifiers, and
+only the operators of the language are allowed between them:
+
+    var total = 10 * 2;
+"#,
+    },
+    ErrorCode {
+        code: "E0004",
+        title: "Invalid utf-8",
+        explanation: r#"The source contains bytes that aren't valid utf-8.
+
+This usually means the file was saved in another encoding, like
+Latin-1 or UTF-16. The offending bytes are skipped, and scanning
+carries on after them. Save the file as utf-8 to fix it.
+"#,
+    },
+    ErrorCode {
+        code: "E0005",
+        title: "Could not read source",
+        explanation: r#"Reading the source failed partway through, for example because the
+file is on a network drive that went away. The message says what
+the operating system reported. Everything read before the failure
+is still checked.
+"#,
+    },
+    ErrorCode {
+        code: "E0006",
+        title: "Expected expression",
+        explanation: r#"The parser found a token where an expression should start.
+
+Erroneous code examples:
+
+    var a = ;
+    print 1 + ;
+    print (2 * 3));
+
+An expression can start with a literal, a name, `(`, `!`, `-`,
+`this` or `super`. Often the problem is a missing operand, or an
+extra `)` or `}` just before the token.
+"#,
+    },
+    ErrorCode {
+        code: "E0007",
+        title: "Expected token",
+        explanation: r#"A token the grammar requires, like a closing `)` or `}`, is missing.
+
+Erroneous code example:
+
+    print add(1, 2;
+
+The error points at the token found instead, which is sometimes on
+a later line than the missing one. Add the token named in the
+message:
+
+    print add(1, 2);
+"#,
+    },
+    ErrorCode {
+        code: "E0008",
+        title: "Invalid assignment target",
+        explanation: r#"The left side of `=` is something that can't be assigned to.
+
+Erroneous code examples:
+
+    1 = 2;
+    a + b = c;
+    f() = 3;
+
+Only a variable, like `a = 1`, or a field of an instance, like
+`point.x = 1`, can be assigned to.
+"#,
+    },
+    ErrorCode {
+        code: "E0009",
+        title: "Too many arguments",
+        explanation: r#"A function declaration has more than 255 parameters, or a call has
+more than 255 arguments.
+
+Pass related values together as fields of an instance instead:
+
+    class Config {}
+    var config = Config();
+    config.width = 80;
+    configure(config);
+"#,
+    },
+    ErrorCode {
+        code: "E0010",
+        title: "Variable already declared",
+        explanation: r#"A local scope declares the same name twice.
+
+Erroneous code example:
+
+    fun f() {
+        var a = 1;
+        var a = 2;
+    }
+
+This is allowed at the top level, to make the prompt convenient, but
+in a block or function it's almost always a mistake. Assign to the
+existing variable, or pick another name:
+
+    fun f() {
+        var a = 1;
+        a = 2;
+    }
+"#,
+    },
+    ErrorCode {
+        code: "E0011",
+        title: "Variable read in its own initializer",
+        explanation: r#"A local variable's initializer refers to the variable itself.
+
+Erroneous code example:
+
+    var a = "outer";
+    {
+        var a = a;
+    }
+
+The new `a` isn't ready to be read until its initializer has run.
+If you meant the outer variable, give the new one another name:
+
+    var a = "outer";
+    {
+        var b = a;
+    }
+"#,
+    },
+    ErrorCode {
+        code: "E0012",
+        title: "Return outside a function",
+        explanation: r#"A `return` statement appears in top-level code.
+
+Erroneous code example:
+
+    print "done";
+    return;
+
+There's no function to return from. To stop a script early, put
+its code in a function and return from that.
+"#,
+    },
+    ErrorCode {
+        code: "E0013",
+        title: "Value returned from an initializer",
+        explanation: r#"An `init` method returns a value.
+
+Erroneous code example:
+
+    class Point {
+        init(x) {
+            this.x = x;
+            return x;
+        }
+    }
+
+Calling a class always produces the new instance, so an initializer
+can't return anything else. A bare `return;` is allowed, to finish
+early.
+"#,
+    },
+    ErrorCode {
+        code: "E0014",
+        title: "`this` outside a class",
+        explanation: r#"`this` is used outside of any method.
+
+Erroneous code example:
+
+    fun f() {
+        print this;
+    }
+
+`this` refers to the instance a method was called on, so it can
+only appear inside a class's methods.
+"#,
+    },
+    ErrorCode {
+        code: "E0015",
+        title: "`super` outside a class",
+        explanation: r#"`super` is used outside of any method.
+
+Erroneous code example:
+
+    fun f() {
+        super.g();
+    }
+
+`super` looks up a method on the superclass of the current class,
+so it can only appear inside a subclass's methods.
+"#,
+    },
+    ErrorCode {
+        code: "E0016",
+        title: "`super` in a class with no superclass",
+        explanation: r#"`super` is used in a class that doesn't inherit from another.
+
+Erroneous code example:
+
+    class A {
+        f() {
+            super.f();
+        }
+    }
+
+Declare the superclass with `<`:
+
+    class Base {
+        f() {}
+    }
+    class A < Base {
+        f() {
+            super.f();
+        }
+    }
+"#,
+    },
+    ErrorCode {
+        code: "E0017",
+        title: "Class inherits from itself",
+        explanation: r#"A class names itself as its superclass.
+
+Erroneous code example:
+
+    class A < A {}
+
+A class can only inherit from another, already declared class.
+"#,
+    },
+    ErrorCode {
+        code: "E0018",
+        title: "Type mismatch",
+        explanation: r#"An operator was applied to values of the wrong type.
+
+Erroneous code examples:
+
+    print 1 + "one";
+    print -"two";
+    print "a" < "b";
+
+Arithmetic and comparison need numbers, and `+` works on two
+numbers or two strings. There are no implicit conversions.
+"#,
+    },
+    ErrorCode {
+        code: "E0019",
+        title: "Undefined variable",
+        explanation: r#"A variable is read or assigned, but was never declared.
+
+Erroneous code example:
+
+    count = 1;
+    print count;
+
+Declare a variable with `var` before using it:
+
+    var count = 1;
+    print count;
+
+Global variables are looked up when the code runs, so a function may
+use a global declared later, as long as it's declared before the
+function is called.
+"#,
+    },
+    ErrorCode {
+        code: "E0020",
+        title: "Value is not callable",
+        explanation: r#"Something that isn't a function or class was called.
+
+Erroneous code example:
+
+    var name = "Ada";
+    name();
+
+Only functions, methods and classes can be called.
+"#,
+    },
+    ErrorCode {
+        code: "E0021",
+        title: "Wrong number of arguments",
+        explanation: r#"A function was called with a different number of arguments than it
+has parameters.
+
+Erroneous code example:
+
+    fun add(a, b) {
+        return a + b;
+    }
+    print add(1);
+
+Every parameter needs an argument. Calling a class passes the
+arguments to its `init` method, or expects none if it has none.
+"#,
+    },
+    ErrorCode {
+        code: "E0022",
+        title: "Stack overflow",
+        explanation: r#"Calls were nested too deeply.
+
+Erroneous code example:
+
+    fun forever(n) {
+        return forever(n + 1);
+    }
+    forever(0);
+
+This is almost always recursion without a base case. Make sure
+every recursive function has a case that returns without calling
+itself, or rewrite it as a loop.
+"#,
+    },
+    ErrorCode {
+        code: "E0023",
+        title: "Not an instance",
+        explanation: r#"A property was read or set on something that isn't an instance.
+
+Erroneous code example:
+
+    var n = 3;
+    print n.value;
+
+Only instances of classes have fields and methods.
+"#,
+    },
+    ErrorCode {
+        code: "E0024",
+        title: "Undefined property",
+        explanation: r#"An instance has no field or method with the given name.
+
+Erroneous code example:
+
+    class Point {}
+    var p = Point();
+    print p.x;
+
+Fields exist once they're assigned, usually in `init`:
+
+    class Point {
+        init() {
+            this.x = 0;
+        }
+    }
+"#,
+    },
+    ErrorCode {
+        code: "E0025",
+        title: "Superclass is not a class",
+        explanation: r#"A class inherits from something that isn't a class.
+
+Erroneous code example:
+
+    var Base = "not a class";
+    class A < Base {}
+
+The name after `<` must refer to a class when the declaration runs.
+"#,
+    },
+    ErrorCode {
+        code: "E0026",
+        title: "Unknown escape sequence",
+        explanation: r#"A `\` in a string literal is followed by a char that doesn't form an
+escape sequence.
+
+Erroneous code example:
+
+    print "C:\temp\new\q";
+
+The escape sequences are:
+
+    \"        a double quote
+    \\        a backslash
+    \n        a newline
+    \t        a tab
+    \r        a carriage return
+    \0        a null char
+    \$        a dollar sign, so "\${" doesn't start an interpolation
+    \u{XXXX}  the char with code point XXXX, in hex
+
+To include a backslash itself, escape it:
+
+    print "C:\\temp\\new\\q";
+"#,
+    },
+    ErrorCode {
+        code: "E0027",
+        title: "Invalid unicode escape",
+        explanation: r#"A `\u` escape in a string literal isn't of the form `\u{XXXX}`.
+
+Erroneous code examples:
+
+    print "\u0041";
+    print "\u{}";
+    print "\u{41";
+    print "\u{1234567}";
+
+The code point is written in hex, with one to six digits, between
+braces:
+
+    print "\u{41}";
+"#,
+    },
+    ErrorCode {
+        code: "E0028",
+        title: "Invalid code point",
+        explanation: r#"A `\u{XXXX}` escape in a string literal names a number that isn't a
+unicode char.
+
+Erroneous code examples:
+
+    print "\u{D800}";
+    print "\u{110000}";
+
+Code points go up to 10FFFF, and the surrogates D800 to DFFF are
+reserved for UTF-16, so aren't chars themselves.
+"#,
+    },
+    ErrorCode {
+        code: "E0029",
+        title: "Number literal too large",
+        explanation: r#"A float literal is too large to store.
+
+Erroneous code example:
+
+    var a = 1e400;
+
+Floats go up to about 1.8e308. Integers and decimals can be any
+size, so a larger number can be written as one of those:
+
+    var a = 1_000_000_000_000_000_000_000_000d;
+"#,
+    },
+    ErrorCode {
+        code: "E0030",
+        title: "Integer overflow",
+        explanation: r#"This error code is no longer emitted.
+
+Arithmetic on two integers used to be an error when the result didn't
+fit in 64 bits. Integers now grow to whatever size they need:
+
+    print 9223372036854775807 + 1;
+"#,
+    },
+    ErrorCode {
+        code: "E0031",
+        title: "Division by zero",
+        explanation: r#"An integer or decimal was divided by zero, with `/` or `%`.
+
+Erroneous code examples:
+
+    print 1 / 0;
+    print 7 % 0;
+    print 1.5 / 0;
+
+There's no exact value to give. Check the divisor first. A float
+divided by zero gives `inf`, `-inf` or `NaN` instead:
+
+    print 1e0 / 0;
+"#,
+    },
+    ErrorCode {
+        code: "E0032",
+        title: "Unterminated block comment",
+        explanation: r#"A block comment was started with `/*`, but the source ended before
+the matching `*/`.
+
+Erroneous code example:
+
+    /* Adds two numbers.
+    fun add(a, b) { return a + b; }
+
+Block comments nest, so each `/*` inside a comment needs its own
+`*/` as well. Close every one:
+
+    /* Adds two numbers. */
+    fun add(a, b) { return a + b; }
+"#,
+    },
+    ErrorCode {
+        code: "E0033",
+        title: "Too deeply nested",
+        explanation: r#"Statements or expressions are nested more than 200 levels deep.
+
+Each nested block or body, parenthesis, operand of a unary operator
+and assigned value counts as a level, so this is hit by code like
+
+    print ((((((((((((((((((((((((((((((1))))))))))))))))))))))))))))));
+
+with hundreds of parentheses. Each link of a chain of operators, calls
+or property accesses counts as a level too, since it nests the chain
+before it, so this is also hit by
+
+    print 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1;
+
+with hundreds of terms. Code like this is almost always generated;
+pull parts of it out into variables or functions instead.
+"#,
+    },
+];
@@ -0,0 +1,364 @@
+/// Errors from every stage, rendered rustc-style with the
+/// source line they point at and an underline under the span:
+///
+///     error[E0007]: Expected ')' after arguments.
+///      --> script.lox:3:15
+///       |
+///     3 | print add(1, 2
+///       |               ^
+///       = help: ...
+///
+use crate::diagnostics::SourceMap;
+use crate::interpreter::{ResolveError, ResolveErrorKind, RuntimeError, RuntimeErrorKind};
+use crate::parser::scanner::Span;
+use crate::parser::{ParseError, ParseErrorKind, Position, ScanError, ScanErrorKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    // Stable code for the kind of error, like E0001.
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub span: Span,
+    // Extra context about the error.
+    pub notes: Vec<String>,
+    // Suggestions for fixing it.
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: &str, span: Span) -> Diagnostic {
+        Diagnostic {
+            code: None,
+            message: message.to_owned(),
+            span,
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help.push(help.to_owned());
+        self
+    }
+
+    /// Renders the diagnostic, ending with a newline. The snippet
+    /// is left out if the source map doesn't have the span's line.
+    pub fn render(&self, source_map: &SourceMap) -> String {
+        let line_number = self.span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        let mut output = match self.code {
+            Some(code) => format!("error[{code}]: {}\n", self.message),
+            None => format!("error: {}\n", self.message),
+        };
+        output += &format!(
+            "{gutter}--> {}:{}:{}\n",
+            source_map.name, self.span.line, self.span.column
+        );
+
+        if let Some((line_start, line_end)) = source_map.line_range(self.span.line) {
+            // A span running past the end of its line is underlined to the end.
+            let start = self.span.start_byte.clamp(line_start, line_end);
+            let end = self.span.end_byte.clamp(start, line_end);
+
+            // Keep tabs in the indent, so the underline lines
+            // up with the code however wide tabs are shown.
+            let indent: String = source_map
+                .text(line_start, start)
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let width = source_map.text(start, end).chars().count().max(1);
+
+            output += &format!("{gutter} |\n");
+            output += &format!(
+                "{line_number} | {}\n",
+                source_map.text(line_start, line_end)
+            );
+            output += &format!("{gutter} | {indent}{}\n", "^".repeat(width));
+        }
+
+        for note in &self.notes {
+            output += &format!("{gutter} = note: {note}\n");
+        }
+        for help in &self.help {
+            output += &format!("{gutter} = help: {help}\n");
+        }
+
+        output
+    }
+
+    /// Renders the diagnostic as a one-line JSON object. Start
+    /// and end positions are of the half-open span, with both
+    /// char and UTF-16 columns, which editors tend to want.
+    pub fn to_json(&self, source_map: &SourceMap) -> String {
+        let start = Position {
+            line: self.span.line,
+            column: self.span.column,
+            utf16_column: self.span.utf16_column,
+            byte_offset: self.span.start_byte,
+        };
+        let end = source_map.position(self.span.end_byte.max(self.span.start_byte));
+
+        let code = match self.code {
+            Some(code) => json_string(code),
+            None => String::from("null"),
+        };
+
+        // Everything we report so far is an error.
+        format!(
+            "{{\"severity\":\"error\",\"code\":{code},\"message\":{},\"file\":{},\
+             \"start\":{},\"end\":{},\"notes\":{},\"help\":{}}}",
+            json_string(&self.message),
+            json_string(&source_map.name),
+            json_position(&start),
+            json_position(&end),
+            json_array(&self.notes),
+            json_array(&self.help),
+        )
+    }
+}
+
+// ---------------------
+// Writing JSON by hand.
+
+fn json_string(string: &str) -> String {
+    let mut json = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+fn json_array(strings: &[String]) -> String {
+    let items: Vec<String> = strings.iter().map(|string| json_string(string)).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn json_position(position: &Position) -> String {
+    format!(
+        "{{\"line\":{},\"column\":{},\"utf16_column\":{},\"byte\":{}}}",
+        position.line, position.column, position.utf16_column, position.byte_offset
+    )
+}
+
+// ---------------------------
+// Conversions from our errors.
+
+impl From<&ScanError> for Diagnostic {
+    fn from(error: &ScanError) -> Self {
+        let diagnostic =
+            Diagnostic::error(&error.message(), error.span).with_code(error.kind.code());
+
+        match error.kind {
+            ScanErrorKind::UnterminatedString => {
+                diagnostic.with_help("Add a closing '\"' to end the string.")
+            }
+            ScanErrorKind::InvalidNumber => diagnostic.with_note(
+                "Numbers look like 42, 3.14, 1_000, 6.02e23, 7d, or 0x1F, 0b1010 and 0o17.",
+            ),
+            ScanErrorKind::NumberOverflow => diagnostic
+                .with_note("Floats go up to about 1.8e308; integers and decimals can be any size."),
+            ScanErrorKind::UnterminatedComment => diagnostic
+                .with_help("Add a closing '*/'; block comments nest, so each '/*' needs one."),
+            ScanErrorKind::InvalidUtf8 => {
+                diagnostic.with_note("Source files must be encoded as utf-8.")
+            }
+
+            ScanErrorKind::UnknownEscape | ScanErrorKind::InvalidUnicodeEscape => diagnostic
+                .with_help("Valid escapes are \\\" \\\\ \\n \\t \\r \\0 \\$ and \\u{XXXX}."),
+            ScanErrorKind::InvalidCodePoint => diagnostic
+                .with_note("Code points go up to 10FFFF, and exclude the surrogates D800 to DFFF."),
+
+            ScanErrorKind::UnexpectedCharacter | ScanErrorKind::Io => diagnostic,
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let diagnostic = Diagnostic::error(&error.message, error.span).with_code(error.kind.code());
+
+        match error.kind {
+            ParseErrorKind::InvalidAssignmentTarget => {
+                diagnostic.with_note("Only variables and fields can be assigned to.")
+            }
+            ParseErrorKind::TooDeeplyNested => {
+                diagnostic.with_help("Move some of the nested code into variables or functions.")
+            }
+            ParseErrorKind::TooManyArguments => {
+                diagnostic.with_note("Functions can have at most 255 parameters.")
+            }
+
+            ParseErrorKind::ExpectedExpression | ParseErrorKind::ExpectedToken => diagnostic,
+        }
+    }
+}
+
+impl From<&ResolveError> for Diagnostic {
+    fn from(error: &ResolveError) -> Self {
+        let diagnostic = Diagnostic::error(&error.message, error.span).with_code(error.kind.code());
+
+        match error.kind {
+            ResolveErrorKind::ReadInOwnInitializer => diagnostic
+                .with_help("Use a different name for the new variable if you meant the outer one."),
+            ResolveErrorKind::ReturnValueFromInitializer => {
+                diagnostic.with_note("An initializer always returns the new instance.")
+            }
+            ResolveErrorKind::SuperWithoutSuperclass => {
+                diagnostic.with_help("Declare a superclass with 'class Name < Superclass'.")
+            }
+
+            ResolveErrorKind::AlreadyDeclared
+            | ResolveErrorKind::ReturnOutsideFunction
+            | ResolveErrorKind::ThisOutsideClass
+            | ResolveErrorKind::SuperOutsideClass
+            | ResolveErrorKind::InheritsFromItself => diagnostic,
+        }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        let diagnostic = Diagnostic::error(&error.message, error.span).with_code(error.kind.code());
+
+        match error.kind {
+            RuntimeErrorKind::UndefinedVariable => {
+                diagnostic.with_help("Declare it with 'var' before using it.")
+            }
+            RuntimeErrorKind::StackOverflow => {
+                diagnostic.with_note("This is usually caused by unbounded recursion.")
+            }
+
+            RuntimeErrorKind::TypeMismatch
+            | RuntimeErrorKind::NotCallable
+            | RuntimeErrorKind::ArityMismatch
+            | RuntimeErrorKind::NotAnInstance
+            | RuntimeErrorKind::UndefinedProperty
+            | RuntimeErrorKind::InvalidSuperclass
+            | RuntimeErrorKind::DivisionByZero => diagnostic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(json_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(json_string(r"C:\dir"), r#""C:\\dir""#);
+        assert_eq!(json_string("a\nb\r\n\tc"), r#""a\nb\r\n\tc""#);
+        assert_eq!(
+            json_string("\0\u{1b}[0m\u{7f}"),
+            r#""\u0000\u001b[0m\u007f""#
+        );
+        // Other chars are valid JSON as they are.
+        assert_eq!(json_string("né 😀"), "\"né 😀\"");
+    }
+
+    #[test]
+    fn writes_one_json_object() {
+        let source_map = SourceMap::new("dir/\"a\".lox", b"print nil + 1;\n".to_vec());
+        let span = Span {
+            line: 1,
+            column: 11,
+            utf16_column: 11,
+            start_byte: 10,
+            end_byte: 11,
+        };
+        let diagnostic = Diagnostic::error("Operands must be numbers.", span)
+            .with_code("E0018")
+            .with_note("Got \"nil\".");
+
+        assert_eq!(
+            diagnostic.to_json(&source_map),
+            "{\"severity\":\"error\",\"code\":\"E0018\",\
+             \"message\":\"Operands must be numbers.\",\"file\":\"dir/\\\"a\\\".lox\",\
+             \"start\":{\"line\":1,\"column\":11,\"utf16_column\":11,\"byte\":10},\
+             \"end\":{\"line\":1,\"column\":12,\"utf16_column\":12,\"byte\":11},\
+             \"notes\":[\"Got \\\"nil\\\".\"],\"help\":[]}"
+        );
+    }
+
+    // The "start" and "end" members of a diagnostic's JSON.
+    fn json_range(source: &str, span: Span) -> String {
+        let source_map = SourceMap::new("test.lox", source.as_bytes().to_vec());
+        let json = Diagnostic::error("", span).to_json(&source_map);
+        let start = json.find("\"start\"").unwrap();
+        let end = json.find(",\"notes\"").unwrap();
+
+        json[start..end].to_owned()
+    }
+
+    #[test]
+    fn counts_end_columns_in_chars_and_utf16_units() {
+        // é is one UTF-16 unit and 😀 is two.
+        let source = "print \"é😀\" + x;";
+
+        // The string literal, with both inside it.
+        let string = Span {
+            line: 1,
+            column: 7,
+            utf16_column: 7,
+            start_byte: 6,
+            end_byte: 14,
+        };
+        assert_eq!(
+            json_range(source, string),
+            "\"start\":{\"line\":1,\"column\":7,\"utf16_column\":7,\"byte\":6},\
+             \"end\":{\"line\":1,\"column\":11,\"utf16_column\":12,\"byte\":14}"
+        );
+
+        // The `x`, after both.
+        let x = Span {
+            line: 1,
+            column: 14,
+            utf16_column: 15,
+            start_byte: 17,
+            end_byte: 18,
+        };
+        assert_eq!(
+            json_range(source, x),
+            "\"start\":{\"line\":1,\"column\":14,\"utf16_column\":15,\"byte\":17},\
+             \"end\":{\"line\":1,\"column\":15,\"utf16_column\":16,\"byte\":18}"
+        );
+    }
+
+    #[test]
+    fn ends_a_span_over_a_line_break_on_the_next_line() {
+        let source = "var s = \"é\n😀";
+        let string = Span {
+            line: 1,
+            column: 9,
+            utf16_column: 9,
+            start_byte: 8,
+            end_byte: source.len(),
+        };
+
+        assert!(json_range(source, string)
+            .ends_with("\"end\":{\"line\":2,\"column\":2,\"utf16_column\":3,\"byte\":16}"));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod codes;
+pub mod diagnostic;
+pub mod source_map;
+
+pub use crate::diagnostics::codes::*;
+pub use crate::diagnostics::diagnostic::*;
+pub use crate::diagnostics::source_map::*;
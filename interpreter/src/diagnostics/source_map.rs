@@ -0,0 +1,85 @@
+/// The text of a script, indexed by line, so errors
+/// can show the source they point at.
+///
+///
+use crate::parser::Position;
+
+use std::fs;
+use std::io;
+
+pub struct SourceMap {
+    // File name to show in messages.
+    pub name: String,
+    // Kept as bytes, since spans are byte offsets
+    // and the source may not be valid utf-8.
+    source: Vec<u8>,
+    // Byte offset where each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    pub fn new(name: &str, source: Vec<u8>) -> SourceMap {
+        let line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == b'\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+
+        SourceMap {
+            name: name.to_owned(),
+            source,
+            line_starts,
+        }
+    }
+
+    pub fn load(path: &str) -> io::Result<SourceMap> {
+        Ok(Self::new(path, fs::read(path)?))
+    }
+
+    /// Byte range of a line (1-based), without its line ending.
+    pub fn line_range(&self, line: usize) -> Option<(usize, usize)> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let mut end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+
+        if end > start && self.source[end - 1] == b'\r' {
+            end -= 1;
+        }
+
+        Some((start, end))
+    }
+
+    /// Line and columns of a byte offset.
+    pub fn position(&self, byte_offset: usize) -> Position {
+        let byte_offset = byte_offset.min(self.source.len());
+
+        // The last line starting at or before the offset.
+        let line = self
+            .line_starts
+            .partition_point(|start| *start <= byte_offset);
+        let line_start = self.line_starts[line - 1];
+        let before = self.text(line_start, byte_offset);
+
+        Position {
+            line,
+            column: before.chars().count() + 1,
+            utf16_column: before.encode_utf16().count() + 1,
+            byte_offset,
+        }
+    }
+
+    /// Source text between two byte offsets, with anything
+    /// that isn't utf-8 shown as replacement chars.
+    pub fn text(&self, start: usize, end: usize) -> String {
+        let end = end.min(self.source.len());
+        let start = start.min(end);
+
+        String::from_utf8_lossy(&self.source[start..end]).into_owned()
+    }
+}
@@ -0,0 +1,164 @@
+/// Values that can be called: user functions, which close
+/// over the scope they're declared in, and native functions.
+///
+///
+use crate::interpreter::{Environment, Interpreter, LoxInstance, RuntimeError, Unwind, Value};
+use crate::parser::grammar::Function;
+use crate::parser::scanner::Span;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Callable: fmt::Display {
+    /// Number of arguments the callable expects.
+    fn arity(&self) -> usize;
+
+    /// The caller has already checked the argument count against `arity`.
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError>;
+}
+
+// ---------------
+// User functions.
+
+pub struct LoxFunction {
+    declaration: Rc<Function>,
+    // The scope the function was declared in.
+    closure: Rc<RefCell<Environment>>,
+    // Initializers always return `this`.
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: Rc<Function>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Makes a method bound to an instance, by closing over
+    /// a new scope in which `this` refers to the instance.
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        environment.define("this", Value::Instance(instance));
+
+        LoxFunction::new(
+            Rc::clone(&self.declaration),
+            Rc::new(RefCell::new(environment)),
+            self.is_initializer,
+        )
+    }
+
+    // The instance a bound initializer was bound to.
+    fn bound_this(&self) -> Value {
+        self.closure
+            .borrow()
+            .get("this", Span::default())
+            .expect("Initializers are always bound.")
+    }
+}
+
+impl Callable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        // Each call gets its own scope, so recursion works.
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(&param.name, argument);
+        }
+
+        match interpreter.execute_block(&self.declaration.body, environment) {
+            Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => Ok(self.bound_this()),
+
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name)
+    }
+}
+
+// -----------------
+// Native functions.
+
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&Interpreter, &[Value]) -> Value,
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        Ok((self.function)(interpreter, &arguments))
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+/// The native functions defined in the global scope.
+pub fn native_functions() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "clock",
+            arity: 0,
+            function: |_, _| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                Value::Float(now.as_secs_f64())
+            },
+        },
+        // Number of arguments given to the script after `--`.
+        NativeFunction {
+            name: "argc",
+            arity: 0,
+            function: |interpreter, _| Value::Int(interpreter.script_args().len() as i64),
+        },
+        // The script argument at an index, or nil if there's none.
+        NativeFunction {
+            name: "arg",
+            arity: 1,
+            function: |interpreter, arguments| match arguments[0] {
+                Value::Int(index) if index >= 0 => interpreter
+                    .script_args()
+                    .get(index as usize)
+                    .map_or(Value::Nil, |arg| Value::String(arg.clone())),
+
+                _ => Value::Nil,
+            },
+        },
+    ]
+}
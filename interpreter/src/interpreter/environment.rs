@@ -0,0 +1,110 @@
+/// Variable storage for the interpreter, as in ch. 8.
+///
+/// Each environment holds one scope's variables and points
+/// to its enclosing scope, so lookups walk outwards until
+/// they find the name.
+///
+use crate::interpreter::{RuntimeError, RuntimeErrorKind, Value};
+use crate::parser::scanner::Span;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    /// A new innermost scope inside `enclosing`.
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    /// Declaring a name again just replaces its value.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    pub fn get(&self, name: &str, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(name) {
+            return Ok(value.clone());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name, span),
+            None => Err(Self::undefined(name, span)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &str, value: Value, span: Span) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value, span),
+            None => Err(Self::undefined(name, span)),
+        }
+    }
+
+    /// Gets a variable the resolver found `distance` scopes out.
+    pub fn get_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &str,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let ancestor = Self::ancestor(environment, distance);
+        let value = ancestor.borrow().values.get(name).cloned();
+
+        value.ok_or_else(|| Self::undefined(name, span))
+    }
+
+    /// Assigns a variable the resolver found `distance` scopes out.
+    pub fn assign_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &str,
+        value: Value,
+    ) {
+        Self::ancestor(environment, distance)
+            .borrow_mut()
+            .define(name, value);
+    }
+
+    fn ancestor(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+    ) -> Rc<RefCell<Environment>> {
+        let mut environment = Rc::clone(environment);
+        for _ in 0..distance {
+            let enclosing = environment
+                .borrow()
+                .enclosing
+                .clone()
+                .expect("The resolver only finds variables in enclosing scopes.");
+            environment = enclosing;
+        }
+
+        environment
+    }
+
+    fn undefined(name: &str, span: Span) -> RuntimeError {
+        RuntimeError::new(
+            RuntimeErrorKind::UndefinedVariable,
+            span,
+            &format!("Undefined variable '{name}'."),
+        )
+    }
+}
@@ -0,0 +1,122 @@
+/// Errors raised while resolving and evaluating a program.
+///
+///
+use crate::interpreter::Value;
+use crate::parser::scanner::Span;
+
+use std::error::Error;
+use std::fmt;
+
+// ---------------
+// Resolver errors.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveErrorKind {
+    // Two declarations of one name in the same local scope.
+    AlreadyDeclared,
+    // `var a = a;` in a local scope.
+    ReadInOwnInitializer,
+    ReturnOutsideFunction,
+    ReturnValueFromInitializer,
+    ThisOutsideClass,
+    SuperOutsideClass,
+    SuperWithoutSuperclass,
+    InheritsFromItself,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveError {
+    pub kind: ResolveErrorKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl ResolveError {
+    pub fn new(kind: ResolveErrorKind, span: Span, message: &str) -> Self {
+        ResolveError {
+            kind,
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Resolve error: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl Error for ResolveError {}
+
+// ---------------
+// Runtime errors.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    // An operator was applied to operands of the wrong type.
+    TypeMismatch,
+    // Reading or assigning a name that was never declared.
+    UndefinedVariable,
+    // Calling something that isn't a function.
+    NotCallable,
+    // Calling a function with the wrong number of arguments.
+    ArityMismatch,
+    // Calls nested too deeply, usually from unbounded recursion.
+    StackOverflow,
+    // Property access on something that isn't an instance.
+    NotAnInstance,
+    // Reading a property that is neither a field nor a method.
+    UndefinedProperty,
+    // Inheriting from something that isn't a class.
+    InvalidSuperclass,
+    // Integer division or remainder by zero.
+    DivisionByZero,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind, span: Span, message: &str) -> Self {
+        RuntimeError {
+            kind,
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Runtime error: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl Error for RuntimeError {}
+
+/// Why execution of a statement stopped early. A return isn't
+/// an error, but it unwinds through enclosing statements the
+/// same way, until the function call catches it.
+pub enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
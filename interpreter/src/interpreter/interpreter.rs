@@ -0,0 +1,754 @@
+/// Tree-walking interpreter for Nystrom's Lox, following
+/// chs. 7 through 13 of *Crafting Interpreters*.
+///
+///
+use crate::interpreter::{
+    native_functions, Callable, Environment, LoxClass, LoxFunction, LoxInstance, Operands,
+    RuntimeError, RuntimeErrorKind, Unwind, Value,
+};
+use crate::number::BigInt;
+use crate::parser::grammar::*;
+use crate::parser::scanner::{NumberLiteral, Span};
+
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Stack size for the thread the interpreter runs on. Each Lox call
+/// recurses through many of our own frames: in a debug build, a call
+/// inside the deepest nesting the parser allows takes about 200 KiB.
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+// We report deep recursion as an error once a call would take us past
+// this much stack, which leaves room for one more call of any shape.
+const STACK_LIMIT: usize = STACK_SIZE - 8 * 1024 * 1024;
+
+// Roughly where the stack is now, from the address of a local.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+pub struct Interpreter {
+    // The outermost scope, where unresolved names live.
+    globals: Rc<RefCell<Environment>>,
+    // The innermost scope we're executing in.
+    environment: Rc<RefCell<Environment>>,
+    // Where the stack was when we started, to measure how much is used.
+    stack_base: usize,
+    // Command-line arguments given to the script.
+    script_args: Vec<String>,
+}
+
+impl Interpreter {
+    pub fn with_script_args(script_args: Vec<String>) -> Self {
+        let mut globals = Environment::new();
+        for function in native_functions() {
+            globals.define(function.name, Value::Callable(Rc::new(function)));
+        }
+
+        let globals = Rc::new(RefCell::new(globals));
+        Interpreter {
+            environment: Rc::clone(&globals),
+            globals,
+            stack_base: stack_address(),
+            script_args,
+        }
+    }
+
+    /// Runs a program, stopping at the first runtime error.
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            match self.execute(statement) {
+                Ok(()) => {}
+
+                Err(Unwind::Error(error)) => return Err(error),
+                // A return outside any function ends the program.
+                Err(Unwind::Return(_)) => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn script_args(&self) -> &[String] {
+        &self.script_args
+    }
+
+    fn execute(&mut self, statement: &Stmt) -> Result<(), Unwind> {
+        statement.accept(self)
+    }
+
+    /// Executes statements in the given scope, then restores
+    /// the current scope, even if a statement fails.
+    pub(crate) fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Environment,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+
+        self.environment = previous;
+
+        result
+    }
+
+    pub fn evaluate(&mut self, expression: &Expression) -> Result<Value, RuntimeError> {
+        expression.accept(self)
+    }
+
+    // Looks up a name at the depth the resolver found,
+    // or in the globals if the resolver didn't find it.
+    fn lookup_variable(
+        &self,
+        name: &str,
+        depth: &Cell<Option<usize>>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        match depth.get() {
+            Some(distance) => Environment::get_at(&self.environment, distance, name, span),
+            None => self.globals.borrow().get(name, span),
+        }
+    }
+
+    fn number_operands(left: &Value, right: &Value, span: Span) -> Result<Operands, RuntimeError> {
+        Operands::promote(left, right).ok_or_else(|| {
+            RuntimeError::new(
+                RuntimeErrorKind::TypeMismatch,
+                span,
+                "Operands must be numbers.",
+            )
+        })
+    }
+
+    // Int arithmetic that overflows an i64 moves to big ints, rather than
+    // wrapping. Division truncates toward zero, and the remainder has the
+    // sign of the left operand. Floats follow IEEE 754 as usual.
+    fn arithmetic(
+        operator: BinaryOp,
+        left: &Value,
+        right: &Value,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let division_by_zero =
+            || RuntimeError::new(RuntimeErrorKind::DivisionByZero, span, "Division by zero.");
+
+        let value = match Self::number_operands(left, right, span)? {
+            Operands::Ints(left, right) => {
+                let result = match operator {
+                    BinaryOp::Plus => left.checked_add(right),
+                    BinaryOp::Minus => left.checked_sub(right),
+                    BinaryOp::Star => left.checked_mul(right),
+                    BinaryOp::Slash | BinaryOp::Percent if right == 0 => {
+                        return Err(division_by_zero())
+                    }
+                    BinaryOp::Slash => left.checked_div(right),
+                    BinaryOp::Percent => left.checked_rem(right),
+                    _ => unreachable!("Not an arithmetic operator."),
+                };
+
+                match result {
+                    Some(result) => Value::Int(result),
+                    None => Self::arithmetic(
+                        operator,
+                        &Value::BigInt(BigInt::from(left)),
+                        &Value::BigInt(BigInt::from(right)),
+                        span,
+                    )?,
+                }
+            }
+
+            Operands::BigInts(left, right) => {
+                let result = match operator {
+                    BinaryOp::Plus => &left + &right,
+                    BinaryOp::Minus => &left - &right,
+                    BinaryOp::Star => &left * &right,
+                    BinaryOp::Slash => left.div_rem(&right).ok_or_else(division_by_zero)?.0,
+                    BinaryOp::Percent => left.div_rem(&right).ok_or_else(division_by_zero)?.1,
+                    _ => unreachable!("Not an arithmetic operator."),
+                };
+
+                Value::from_big_int(result)
+            }
+
+            Operands::Decimals(left, right) => {
+                let result = match operator {
+                    BinaryOp::Plus => &left + &right,
+                    BinaryOp::Minus => &left - &right,
+                    BinaryOp::Star => &left * &right,
+                    BinaryOp::Slash => left.checked_div(&right).ok_or_else(division_by_zero)?,
+                    BinaryOp::Percent => left.checked_rem(&right).ok_or_else(division_by_zero)?,
+                    _ => unreachable!("Not an arithmetic operator."),
+                };
+
+                Value::Decimal(result)
+            }
+
+            Operands::Floats(left, right) => {
+                let result = match operator {
+                    BinaryOp::Plus => left + right,
+                    BinaryOp::Minus => left - right,
+                    BinaryOp::Star => left * right,
+                    BinaryOp::Slash => left / right,
+                    BinaryOp::Percent => left % right,
+                    _ => unreachable!("Not an arithmetic operator."),
+                };
+
+                Value::Float(result)
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn comparison(
+        operator: BinaryOp,
+        left: &Value,
+        right: &Value,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        // Comparisons with NaN are unordered, so always false.
+        let ordering = Self::number_operands(left, right, span)?.compare();
+
+        let result = match operator {
+            BinaryOp::Less => ordering == Some(Ordering::Less),
+            BinaryOp::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            BinaryOp::Greater => ordering == Some(Ordering::Greater),
+            BinaryOp::GreaterEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+            _ => unreachable!("Not a comparison operator."),
+        };
+
+        Ok(Value::Bool(result))
+    }
+}
+
+impl Visitor<Result<Value, RuntimeError>> for Interpreter {
+    fn visit_literal(&mut self, expression: &Literal) -> Result<Value, RuntimeError> {
+        let value = match expression {
+            Literal::Number(NumberLiteral::Int(number)) => Value::Int(*number),
+            Literal::Number(NumberLiteral::BigInt(number)) => Value::BigInt(number.clone()),
+            Literal::Number(NumberLiteral::Float(number)) => Value::Float(*number),
+            Literal::Number(NumberLiteral::Decimal(number)) => Value::Decimal(number.clone()),
+            Literal::String(string) => Value::String(string.clone()),
+
+            Literal::True => Value::Bool(true),
+            Literal::False => Value::Bool(false),
+            Literal::Nil => Value::Nil,
+        };
+
+        Ok(value)
+    }
+
+    fn visit_unary(&mut self, expression: &Unary) -> Result<Value, RuntimeError> {
+        let right = expression.expr.accept(self)?;
+
+        match expression.operator {
+            UnaryOp::Bang => Ok(Value::Bool(!right.is_truthy())),
+            UnaryOp::Minus => match right {
+                // Only i64::MIN overflows, becoming a big int.
+                Value::Int(number) => Ok(number
+                    .checked_neg()
+                    .map_or_else(|| Value::BigInt(-&BigInt::from(number)), Value::Int)),
+                Value::BigInt(number) => Ok(Value::from_big_int(-&number)),
+                Value::Float(number) => Ok(Value::Float(-number)),
+                Value::Decimal(number) => Ok(Value::Decimal(-&number)),
+
+                _ => Err(RuntimeError::new(
+                    RuntimeErrorKind::TypeMismatch,
+                    expression.operator_span,
+                    "Operand must be a number.",
+                )),
+            },
+        }
+    }
+
+    fn visit_binary(&mut self, expression: &Binary) -> Result<Value, RuntimeError> {
+        let left = expression.left.accept(self)?;
+        let right = expression.right.accept(self)?;
+        let span = expression.operator_span;
+
+        let value = match expression.operator {
+            BinaryOp::EqualEqual => Value::Bool(left == right),
+            BinaryOp::BangEqual => Value::Bool(left != right),
+
+            BinaryOp::Plus => match (left, right) {
+                (Value::String(left), Value::String(right)) => Value::String(left + &right),
+                (left, right) if left.is_number() && right.is_number() => {
+                    Self::arithmetic(BinaryOp::Plus, &left, &right, span)?
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::TypeMismatch,
+                        span,
+                        "Operands must be two numbers or two strings.",
+                    ))
+                }
+            },
+
+            BinaryOp::Minus | BinaryOp::Star | BinaryOp::Slash | BinaryOp::Percent => {
+                Self::arithmetic(expression.operator, &left, &right, span)?
+            }
+
+            BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => {
+                Self::comparison(expression.operator, &left, &right, span)?
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn _visit_grouping_impl(&mut self, expression: &Expression) -> Result<Value, RuntimeError> {
+        expression.accept(self)
+    }
+
+    fn visit_variable(&mut self, expression: &Variable) -> Result<Value, RuntimeError> {
+        self.lookup_variable(&expression.name, &expression.depth, expression.span)
+    }
+
+    fn visit_assign(&mut self, expression: &Assign) -> Result<Value, RuntimeError> {
+        let value = expression.value.accept(self)?;
+        match expression.depth.get() {
+            Some(distance) => {
+                Environment::assign_at(&self.environment, distance, &expression.name, value.clone())
+            }
+            None => self.globals.borrow_mut().assign(
+                &expression.name,
+                value.clone(),
+                expression.name_span,
+            )?,
+        }
+
+        // Assignment is an expression, whose value is the assigned value.
+        Ok(value)
+    }
+
+    fn visit_call(&mut self, expression: &Call) -> Result<Value, RuntimeError> {
+        let callee = expression.callee.accept(self)?;
+
+        let mut arguments = vec![];
+        for argument in &expression.arguments {
+            arguments.push(argument.accept(self)?);
+        }
+
+        let function: Rc<dyn Callable> = match callee {
+            Value::Callable(function) => function,
+            Value::Class(class) => Rc::new(class),
+
+            _ => {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::NotCallable,
+                    expression.paren_span,
+                    "Can only call functions and classes.",
+                ))
+            }
+        };
+
+        if arguments.len() != function.arity() {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::ArityMismatch,
+                expression.paren_span,
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                ),
+            ));
+        }
+
+        if self.stack_base.abs_diff(stack_address()) >= STACK_LIMIT {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::StackOverflow,
+                expression.paren_span,
+                "Stack overflow.",
+            ));
+        }
+
+        function.call(self, arguments)
+    }
+
+    fn visit_get(&mut self, expression: &Get) -> Result<Value, RuntimeError> {
+        match expression.object.accept(self)? {
+            Value::Instance(instance) => {
+                LoxInstance::get(&instance, &expression.name, expression.name_span)
+            }
+
+            _ => Err(RuntimeError::new(
+                RuntimeErrorKind::NotAnInstance,
+                expression.name_span,
+                "Only instances have properties.",
+            )),
+        }
+    }
+
+    fn visit_set(&mut self, expression: &Set) -> Result<Value, RuntimeError> {
+        let Value::Instance(instance) = expression.object.accept(self)? else {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::NotAnInstance,
+                expression.name_span,
+                "Only instances have fields.",
+            ));
+        };
+
+        let value = expression.value.accept(self)?;
+        instance.borrow_mut().set(&expression.name, value.clone());
+
+        Ok(value)
+    }
+
+    fn visit_this(&mut self, expression: &This) -> Result<Value, RuntimeError> {
+        self.lookup_variable("this", &expression.depth, expression.span)
+    }
+
+    fn visit_super(&mut self, expression: &Super) -> Result<Value, RuntimeError> {
+        // The scope binding 'this' is always just inside the one binding 'super'.
+        let distance = expression
+            .depth
+            .get()
+            .expect("The resolver binds every 'super' expression.");
+        let superclass = Environment::get_at(
+            &self.environment,
+            distance,
+            "super",
+            expression.keyword_span,
+        )?;
+        let this = Environment::get_at(
+            &self.environment,
+            distance - 1,
+            "this",
+            expression.keyword_span,
+        )?;
+
+        let (Value::Class(superclass), Value::Instance(instance)) = (superclass, this) else {
+            unreachable!("'super' and 'this' are only bound by the interpreter.");
+        };
+
+        match superclass.find_method(&expression.method) {
+            Some(method) => Ok(Value::Callable(Rc::new(method.bind(instance)))),
+
+            None => Err(RuntimeError::new(
+                RuntimeErrorKind::UndefinedProperty,
+                expression.method_span,
+                &format!("Undefined property '{}'.", expression.method),
+            )),
+        }
+    }
+
+    // Each interpolated value is stringified as `print` would show it.
+    fn visit_interpolation(&mut self, expression: &Interpolation) -> Result<Value, RuntimeError> {
+        let mut string = String::new();
+        for part in &expression.parts {
+            match part {
+                InterpolationPart::Text(text) => string.push_str(text),
+                InterpolationPart::Expression(expression) => {
+                    string += &self.evaluate(expression)?.to_string();
+                }
+            }
+        }
+
+        Ok(Value::String(string))
+    }
+
+    fn visit_logical(&mut self, expression: &Logical) -> Result<Value, RuntimeError> {
+        let left = expression.left.accept(self)?;
+
+        // Short-circuit, returning the deciding operand itself.
+        let short_circuits = match expression.operator {
+            LogicalOp::Or => left.is_truthy(),
+            LogicalOp::And => !left.is_truthy(),
+        };
+        if short_circuits {
+            return Ok(left);
+        }
+
+        expression.right.accept(self)
+    }
+}
+
+impl StmtVisitor<Result<(), Unwind>> for Interpreter {
+    fn visit_expression_stmt(&mut self, expression: &Expression) -> Result<(), Unwind> {
+        self.evaluate(expression)?;
+
+        Ok(())
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expression) -> Result<(), Unwind> {
+        let value = self.evaluate(expression)?;
+        println!("{value}");
+
+        Ok(())
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &Var) -> Result<(), Unwind> {
+        let value = match &stmt.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Value::Nil,
+        };
+        self.environment.borrow_mut().define(&stmt.name, value);
+
+        Ok(())
+    }
+
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) -> Result<(), Unwind> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+        self.execute_block(statements, environment)
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If) -> Result<(), Unwind> {
+        if self.evaluate(&stmt.condition)?.is_truthy() {
+            self.execute(&stmt.then_branch)
+        } else if let Some(else_branch) = &stmt.else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While) -> Result<(), Unwind> {
+        while self.evaluate(&stmt.condition)?.is_truthy() {
+            self.execute(&stmt.body)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<Function>) -> Result<(), Unwind> {
+        let function = LoxFunction::new(Rc::clone(stmt), Rc::clone(&self.environment), false);
+        self.environment
+            .borrow_mut()
+            .define(&stmt.name, Value::Callable(Rc::new(function)));
+
+        Ok(())
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return) -> Result<(), Unwind> {
+        let value = match &stmt.value {
+            Some(value) => self.evaluate(value)?,
+            None => Value::Nil,
+        };
+
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Class) -> Result<(), Unwind> {
+        let superclass = match &stmt.superclass {
+            Some(variable) => match self.visit_variable(variable)? {
+                Value::Class(class) => Some(class),
+
+                _ => {
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::InvalidSuperclass,
+                        variable.span,
+                        "Superclass must be a class.",
+                    )
+                    .into())
+                }
+            },
+            None => None,
+        };
+
+        // Defining the name first lets methods refer to the class.
+        self.environment.borrow_mut().define(&stmt.name, Value::Nil);
+
+        // Methods of a subclass close over a scope binding 'super'.
+        let mut closure = Rc::clone(&self.environment);
+        if let Some(superclass) = &superclass {
+            let mut environment = Environment::with_enclosing(closure);
+            environment.define("super", Value::Class(Rc::clone(superclass)));
+            closure = Rc::new(RefCell::new(environment));
+        }
+
+        let mut methods = HashMap::new();
+        for method in &stmt.methods {
+            let function = LoxFunction::new(
+                Rc::clone(method),
+                Rc::clone(&closure),
+                method.name == "init",
+            );
+            methods.insert(method.name.clone(), Rc::new(function));
+        }
+
+        let class = LoxClass::new(&stmt.name, superclass, methods);
+        self.environment
+            .borrow_mut()
+            .define(&stmt.name, Value::Class(Rc::new(class)));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::interpreter::Resolver;
+    use crate::parser::scanner::Scanner;
+    use crate::parser::Parser;
+
+    fn eval(expression: &str) -> Result<Value, RuntimeError> {
+        let mut scanner = Scanner::from_str(&format!("{expression};"));
+        assert!(scanner.scan_tokens().is_empty());
+        let statements = Parser::new(scanner.tokens).parse_program().unwrap();
+        Resolver::new().resolve(&statements).unwrap();
+
+        let [Stmt::Expression(expression)] = &statements[..] else {
+            panic!("Expected one expression.");
+        };
+        Interpreter::with_script_args(vec![]).evaluate(expression)
+    }
+
+    // The type and printed value of an expression.
+    fn number(expression: &str) -> (&'static str, String) {
+        let value = eval(expression).unwrap();
+        let kind = match value {
+            Value::Int(_) => "int",
+            Value::BigInt(_) => "big int",
+            Value::Float(_) => "float",
+            Value::Decimal(_) => "decimal",
+            _ => panic!("{expression} isn't a number."),
+        };
+
+        (kind, value.to_string())
+    }
+
+    fn number_cases(cases: &[(&str, &'static str, &str)]) {
+        for (expression, kind, value) in cases {
+            assert_eq!(
+                number(expression),
+                (*kind, value.to_string()),
+                "{expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn promotes_to_the_later_type() {
+        number_cases(&[
+            ("1 + 2", "int", "3"),
+            ("1 + 2.5e0", "float", "3.5"),
+            ("2.5e0 * 2", "float", "5.0"),
+            ("1 + 0.5", "decimal", "1.5"),
+            ("0.5 - 1e0", "float", "-0.5"),
+            ("-2 * 1.25", "decimal", "-2.50"),
+        ]);
+    }
+
+    #[test]
+    fn truncates_int_division() {
+        number_cases(&[
+            ("7 / 2", "int", "3"),
+            ("-7 / 2", "int", "-3"),
+            ("7 / -2", "int", "-3"),
+            ("7e0 / 2", "float", "3.5"),
+            ("7 / 2d", "decimal", "3.5"),
+        ]);
+    }
+
+    #[test]
+    fn gives_remainders_the_sign_of_the_left_operand() {
+        number_cases(&[
+            ("7 % 3", "int", "1"),
+            ("-7 % 3", "int", "-1"),
+            ("7 % -3", "int", "1"),
+            ("-7.5 % 2", "decimal", "-1.5"),
+            ("-7.5e0 % 2", "float", "-1.5"),
+        ]);
+    }
+
+    #[test]
+    fn reports_exact_division_by_zero() {
+        for expression in ["1 / 0", "1 % 0", "1.5 / 0", "1d % 0.0"] {
+            let error = eval(expression).unwrap_err();
+            assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero, "{expression}");
+        }
+    }
+
+    #[test]
+    fn divides_floats_by_zero() {
+        number_cases(&[
+            ("1e0 / 0", "float", "inf"),
+            ("-1e0 / 0", "float", "-inf"),
+            ("0e0 / 0", "float", "NaN"),
+        ]);
+    }
+
+    #[test]
+    fn compares_numbers_across_types() {
+        for expression in [
+            "1 == 1e0",
+            "1 == 1.0",
+            "1.0 == 1e0",
+            "1 != 1.5",
+            "1 < 1.5",
+            "2 > 1e0",
+            "9223372036854775808 > 1e0",
+            "1 <= 1.00",
+        ] {
+            assert!(
+                eval(expression).unwrap() == Value::Bool(true),
+                "{expression}"
+            );
+        }
+
+        // Comparisons with NaN are always false, and it's not even equal to itself.
+        for expression in [
+            "0e0 / 0 < 1",
+            "0e0 / 0 >= 1",
+            "0e0 / 0 == 0e0 / 0",
+            "\"1\" == 1",
+        ] {
+            assert!(
+                eval(expression).unwrap() == Value::Bool(false),
+                "{expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn moves_ints_that_overflow_to_big_ints_and_back() {
+        number_cases(&[
+            ("9223372036854775807 + 1", "big int", "9223372036854775808"),
+            ("9223372036854775807 + 1 - 1", "int", "9223372036854775807"),
+            ("4294967296 * 4294967296", "big int", "18446744073709551616"),
+            ("4294967296 * 4294967296 / 4294967296", "int", "4294967296"),
+            (
+                "-9223372036854775807 - 2",
+                "big int",
+                "-9223372036854775809",
+            ),
+            (
+                "-9223372036854775807 - 2 + 1",
+                "int",
+                "-9223372036854775808",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn handles_the_smallest_int() {
+        // The literal is a big int, but negating it makes it fit.
+        number_cases(&[
+            ("-9223372036854775808", "int", "-9223372036854775808"),
+            ("-(-9223372036854775808)", "big int", "9223372036854775808"),
+            ("-(-9223372036854775808) - 1", "int", "9223372036854775807"),
+            (
+                "-9223372036854775808 / -1",
+                "big int",
+                "9223372036854775808",
+            ),
+            ("-9223372036854775808 % -1", "int", "0"),
+            (
+                "-9223372036854775808 * -1",
+                "big int",
+                "9223372036854775808",
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod callable;
+pub mod class;
+pub mod environment;
+pub mod error;
+#[allow(clippy::module_inception)]
+pub mod interpreter;
+pub mod resolver;
+pub mod value;
+
+pub use crate::interpreter::callable::*;
+pub use crate::interpreter::class::*;
+pub use crate::interpreter::environment::*;
+pub use crate::interpreter::error::*;
+pub use crate::interpreter::interpreter::*;
+pub use crate::interpreter::resolver::*;
+pub use crate::interpreter::value::*;
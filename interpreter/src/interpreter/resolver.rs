@@ -0,0 +1,470 @@
+/// Static pass between parsing and execution, as in ch. 11.
+///
+/// It walks the AST once, recording for each variable reference
+/// how many scopes out its declaration is, so closures see the
+/// binding that was in scope where they were written. Along the
+/// way it reports errors we can catch before running anything.
+///
+use crate::interpreter::{ResolveError, ResolveErrorKind};
+use crate::parser::grammar::*;
+use crate::parser::scanner::Span;
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+pub struct Resolver {
+    // Stack of local scopes. A name maps to whether its
+    // initializer has finished, so it's ready to be read.
+    // Globals aren't tracked.
+    scopes: Vec<HashMap<String, bool>>,
+
+    current_function: FunctionType,
+    current_class: ClassType,
+
+    errors: Vec<ResolveError>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Resolver {
+            scopes: vec![],
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            errors: vec![],
+        }
+    }
+
+    /// Resolves a whole program, reporting every error found.
+    pub fn resolve(&mut self, statements: &[Stmt]) -> Result<(), Vec<ResolveError>> {
+        self.resolve_statements(statements);
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            statement.accept(self);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str, span: Span) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        if scope.contains_key(name) {
+            self.errors.push(ResolveError::new(
+                ResolveErrorKind::AlreadyDeclared,
+                span,
+                &format!("Already a variable named '{name}' in this scope."),
+            ));
+        }
+        scope.insert(name.to_owned(), false);
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_owned(), true);
+        }
+    }
+
+    // If the name isn't found in any scope, we leave
+    // the depth unset and assume it's a global.
+    fn resolve_local(&mut self, name: &str, depth: &Cell<Option<usize>>) {
+        for (distance, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(name) {
+                depth.set(Some(distance));
+                return;
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, function: &Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(&param.name, param.span);
+            self.define(&param.name);
+        }
+        self.resolve_statements(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn error(&mut self, kind: ResolveErrorKind, span: Span, message: &str) {
+        self.errors.push(ResolveError::new(kind, span, message));
+    }
+}
+
+impl Visitor<()> for Resolver {
+    fn visit_literal(&mut self, _expression: &Literal) {}
+
+    fn visit_unary(&mut self, expression: &Unary) {
+        expression.expr.accept(self);
+    }
+
+    fn visit_binary(&mut self, expression: &Binary) {
+        expression.left.accept(self);
+        expression.right.accept(self);
+    }
+
+    fn _visit_grouping_impl(&mut self, expression: &Expression) {
+        expression.accept(self);
+    }
+
+    fn visit_variable(&mut self, expression: &Variable) {
+        let in_own_initializer = self
+            .scopes
+            .last()
+            .is_some_and(|scope| scope.get(&expression.name) == Some(&false));
+        if in_own_initializer {
+            self.error(
+                ResolveErrorKind::ReadInOwnInitializer,
+                expression.span,
+                "Can't read local variable in its own initializer.",
+            );
+        }
+
+        self.resolve_local(&expression.name, &expression.depth);
+    }
+
+    fn visit_assign(&mut self, expression: &Assign) {
+        expression.value.accept(self);
+        self.resolve_local(&expression.name, &expression.depth);
+    }
+
+    fn visit_logical(&mut self, expression: &Logical) {
+        expression.left.accept(self);
+        expression.right.accept(self);
+    }
+
+    fn visit_call(&mut self, expression: &Call) {
+        expression.callee.accept(self);
+        for argument in &expression.arguments {
+            argument.accept(self);
+        }
+    }
+
+    // Properties are looked up dynamically, so only the object is resolved.
+    fn visit_get(&mut self, expression: &Get) {
+        expression.object.accept(self);
+    }
+
+    fn visit_set(&mut self, expression: &Set) {
+        expression.value.accept(self);
+        expression.object.accept(self);
+    }
+
+    fn visit_this(&mut self, expression: &This) {
+        if self.current_class == ClassType::None {
+            self.error(
+                ResolveErrorKind::ThisOutsideClass,
+                expression.span,
+                "Can't use 'this' outside of a class.",
+            );
+            return;
+        }
+
+        self.resolve_local("this", &expression.depth);
+    }
+
+    fn visit_interpolation(&mut self, expression: &Interpolation) {
+        for part in &expression.parts {
+            if let InterpolationPart::Expression(expression) = part {
+                expression.accept(self);
+            }
+        }
+    }
+
+    fn visit_super(&mut self, expression: &Super) {
+        match self.current_class {
+            ClassType::None => self.error(
+                ResolveErrorKind::SuperOutsideClass,
+                expression.keyword_span,
+                "Can't use 'super' outside of a class.",
+            ),
+            ClassType::Class => self.error(
+                ResolveErrorKind::SuperWithoutSuperclass,
+                expression.keyword_span,
+                "Can't use 'super' in a class with no superclass.",
+            ),
+
+            ClassType::Subclass => self.resolve_local("super", &expression.depth),
+        }
+    }
+}
+
+impl StmtVisitor<()> for Resolver {
+    fn visit_expression_stmt(&mut self, expression: &Expression) {
+        expression.accept(self);
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expression) {
+        expression.accept(self);
+    }
+
+    // Declaring and defining separately catches
+    // reads of a variable in its own initializer.
+    fn visit_var_stmt(&mut self, stmt: &Var) {
+        self.declare(&stmt.name, stmt.name_span);
+        if let Some(initializer) = &stmt.initializer {
+            initializer.accept(self);
+        }
+        self.define(&stmt.name);
+    }
+
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) {
+        self.begin_scope();
+        self.resolve_statements(statements);
+        self.end_scope();
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If) {
+        stmt.condition.accept(self);
+        stmt.then_branch.accept(self);
+        if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While) {
+        stmt.condition.accept(self);
+        stmt.body.accept(self);
+    }
+
+    // A function can refer to itself, so we define
+    // its name before resolving the body.
+    fn visit_function_stmt(&mut self, stmt: &std::rc::Rc<Function>) {
+        self.declare(&stmt.name, stmt.name_span);
+        self.define(&stmt.name);
+
+        self.resolve_function(stmt, FunctionType::Function);
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return) {
+        if self.current_function == FunctionType::None {
+            self.error(
+                ResolveErrorKind::ReturnOutsideFunction,
+                stmt.keyword_span,
+                "Can't return from top-level code.",
+            );
+        }
+
+        if let Some(value) = &stmt.value {
+            if self.current_function == FunctionType::Initializer {
+                self.error(
+                    ResolveErrorKind::ReturnValueFromInitializer,
+                    stmt.keyword_span,
+                    "Can't return a value from an initializer.",
+                );
+            }
+
+            value.accept(self);
+        }
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Class) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(&stmt.name, stmt.name_span);
+        self.define(&stmt.name);
+
+        // Methods of a subclass are inside a scope binding 'super'.
+        if let Some(superclass) = &stmt.superclass {
+            if superclass.name == stmt.name {
+                self.error(
+                    ResolveErrorKind::InheritsFromItself,
+                    superclass.span,
+                    "A class can't inherit from itself.",
+                );
+            }
+
+            self.current_class = ClassType::Subclass;
+            self.visit_variable(superclass);
+
+            self.begin_scope();
+            self.define("super");
+        }
+
+        // And methods are inside a scope binding 'this'.
+        self.begin_scope();
+        self.define("this");
+
+        for method in &stmt.methods {
+            let function_type = if method.name == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, function_type);
+        }
+
+        self.end_scope();
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::interpreter::{Interpreter, Value};
+    use crate::parser::scanner::Scanner;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        let mut scanner = Scanner::from_str(source);
+        assert!(scanner.scan_tokens().is_empty());
+        Parser::new(scanner.tokens).parse_program().unwrap()
+    }
+
+    // Kind, line and column of each error.
+    fn errors(source: &str) -> Vec<(ResolveErrorKind, usize, usize)> {
+        match Resolver::new().resolve(&parse(source)) {
+            Ok(()) => vec![],
+            Err(errors) => errors
+                .into_iter()
+                .map(|error| (error.kind, error.span.line, error.span.column))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn binds_closures_where_they_are_written() {
+        // The book's example, with `showA` recording what it sees.
+        let mut statements = parse(
+            r#"
+            var a = "global";
+            var seen = "";
+            {
+                fun showA() {
+                    seen = seen + a + " ";
+                }
+
+                showA();
+                var a = "block";
+                showA();
+            }
+            seen;
+            "#,
+        );
+        Resolver::new().resolve(&statements).unwrap();
+
+        let Some(Stmt::Expression(seen)) = statements.pop() else {
+            panic!("The program ends with an expression.");
+        };
+        let mut interpreter = Interpreter::with_script_args(vec![]);
+        interpreter.interpret(&statements).unwrap();
+        let Ok(Value::String(seen)) = interpreter.evaluate(&seen) else {
+            panic!("'seen' is a string.");
+        };
+
+        assert_eq!(seen, "global global ");
+    }
+
+    #[test]
+    fn reports_reading_a_local_in_its_own_initializer() {
+        assert_eq!(
+            errors("{ var a = 1; { var a = a; } }"),
+            [(ResolveErrorKind::ReadInOwnInitializer, 1, 24)]
+        );
+        // Globals can be redefined in terms of themselves.
+        assert_eq!(errors("var a = 1; var a = a;"), []);
+    }
+
+    #[test]
+    fn reports_returning_from_top_level_code() {
+        assert_eq!(
+            errors("print 1;\nreturn;"),
+            [(ResolveErrorKind::ReturnOutsideFunction, 2, 1)]
+        );
+        assert_eq!(errors("fun f() { return 1; }"), []);
+    }
+
+    #[test]
+    fn reports_returning_a_value_from_an_initializer() {
+        assert_eq!(
+            errors("class A { init() { return 1; } }"),
+            [(ResolveErrorKind::ReturnValueFromInitializer, 1, 20)]
+        );
+        // An early return without a value is fine.
+        assert_eq!(errors("class A { init() { return; } }"), []);
+    }
+
+    #[test]
+    fn reports_this_and_super_outside_their_classes() {
+        assert_eq!(
+            errors("print this;"),
+            [(ResolveErrorKind::ThisOutsideClass, 1, 7)]
+        );
+        assert_eq!(
+            errors("fun f() { super.g(); }"),
+            [(ResolveErrorKind::SuperOutsideClass, 1, 11)]
+        );
+        assert_eq!(
+            errors("class A { f() { super.f(); } }"),
+            [(ResolveErrorKind::SuperWithoutSuperclass, 1, 17)]
+        );
+        assert_eq!(
+            errors("class A { f() {} }\nclass B < A { f() { super.f(); this.g(); } }"),
+            []
+        );
+    }
+
+    #[test]
+    fn reports_a_class_inheriting_from_itself() {
+        assert_eq!(
+            errors("class A < A {}"),
+            [(ResolveErrorKind::InheritsFromItself, 1, 11)]
+        );
+    }
+
+    #[test]
+    fn reports_declaring_a_local_twice() {
+        assert_eq!(
+            errors("{ var a = 1;\n  var a = 2; }"),
+            [(ResolveErrorKind::AlreadyDeclared, 2, 7)]
+        );
+        assert_eq!(
+            errors("fun f(a, a) {}"),
+            [(ResolveErrorKind::AlreadyDeclared, 1, 10)]
+        );
+        // Shadowing in an inner scope is fine.
+        assert_eq!(errors("{ var a = 1; { var a = 2; } }"), []);
+    }
+}
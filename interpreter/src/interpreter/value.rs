@@ -0,0 +1,161 @@
+/// Runtime values produced by evaluating expressions.
+///
+///
+use crate::interpreter::{Callable, LoxClass, LoxInstance};
+use crate::number::{BigInt, Decimal};
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub enum Value {
+    Int(i64),
+    // Only for ints outside the range of an i64.
+    BigInt(BigInt),
+    Float(f64),
+    Decimal(Decimal),
+    String(String),
+    Bool(bool),
+    Nil,
+    Callable(Rc<dyn Callable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+}
+
+impl Value {
+    /// Lox truthiness: `nil` and `false` are falsey,
+    /// everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_)
+        )
+    }
+
+    /// An int value, which is only a big int if it has to be.
+    pub fn from_big_int(number: BigInt) -> Value {
+        number.to_i64().map_or(Value::BigInt(number), Value::Int)
+    }
+
+    fn to_big_int(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(number) => Some(BigInt::from(*number)),
+            Value::BigInt(number) => Some(number.clone()),
+            _ => None,
+        }
+    }
+
+    fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Decimal(number) => Some(number.clone()),
+            _ => self.to_big_int().map(Decimal::from),
+        }
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(number) => Some(*number as f64),
+            Value::BigInt(number) => Some(number.to_f64()),
+            Value::Float(number) => Some(*number),
+            Value::Decimal(number) => Some(number.to_f64()),
+            _ => None,
+        }
+    }
+}
+
+/// The operands of a numeric operator, promoted to the same type. The
+/// types go int, big int, decimal, float, and the operands take the
+/// later of their two types, so only floats are ever inexact.
+pub enum Operands {
+    Ints(i64, i64),
+    BigInts(BigInt, BigInt),
+    Decimals(Decimal, Decimal),
+    Floats(f64, f64),
+}
+
+impl Operands {
+    /// None if either value isn't a number.
+    pub fn promote(left: &Value, right: &Value) -> Option<Operands> {
+        let is = |test: fn(&Value) -> bool| test(left) || test(right);
+
+        match (left, right) {
+            (Value::Int(left), Value::Int(right)) => Some(Operands::Ints(*left, *right)),
+
+            _ if is(|value| matches!(value, Value::Float(_))) => {
+                Some(Operands::Floats(left.to_f64()?, right.to_f64()?))
+            }
+            _ if is(|value| matches!(value, Value::Decimal(_))) => {
+                Some(Operands::Decimals(left.to_decimal()?, right.to_decimal()?))
+            }
+            _ => Some(Operands::BigInts(left.to_big_int()?, right.to_big_int()?)),
+        }
+    }
+
+    /// None when either is NaN.
+    pub fn compare(&self) -> Option<Ordering> {
+        match self {
+            Operands::Ints(left, right) => Some(left.cmp(right)),
+            Operands::BigInts(left, right) => Some(left.cmp(right)),
+            Operands::Decimals(left, right) => Some(left.cmp(right)),
+            Operands::Floats(left, right) => left.partial_cmp(right),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Numbers of different types are equal if their values are.
+            (left, right) if left.is_number() && right.is_number() => {
+                Operands::promote(left, right).and_then(|operands| operands.compare())
+                    == Some(Ordering::Equal)
+            }
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Nil, Value::Nil) => true,
+            // Objects are only equal to themselves.
+            (Value::Callable(left), Value::Callable(right)) => Rc::ptr_eq(left, right),
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+
+            _ => false,
+        }
+    }
+}
+
+// Closures and instances can refer back to themselves,
+// so we don't derive Debug and walk into their environments.
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "{string:?}"),
+
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(number) => write!(f, "{number}"),
+            Value::BigInt(number) => write!(f, "{number}"),
+            // Debug formatting keeps the ".0" on whole floats,
+            // so they're told apart from ints.
+            Value::Float(number) => write!(f, "{number:?}"),
+            Value::Decimal(number) => write!(f, "{number}"),
+            Value::String(string) => write!(f, "{string}"),
+            Value::Bool(bool) => write!(f, "{bool}"),
+            Value::Nil => write!(f, "nil"),
+            Value::Callable(callable) => write!(f, "{callable}"),
+            Value::Class(class) => write!(f, "{class}"),
+            Value::Instance(instance) => write!(f, "{}", instance.borrow()),
+        }
+    }
+}
@@ -6,60 +6,282 @@
 ///
 /// Created by sean on 12/18/2024.
 ///
+mod cli;
+mod diagnostics;
+mod interpreter;
+mod number;
 mod parser;
+mod repl;
 
+use std::cell::Cell;
 use std::env;
 use std::error::Error;
 use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::process;
+use std::thread;
 
-use crate::parser::scanner::Scanner;
+use crate::cli::{Command, ErrorFormat, Options, USAGE};
+use crate::diagnostics::{Diagnostic, ErrorCode, SourceMap};
+use crate::interpreter::{Interpreter, Resolver, STACK_SIZE};
+use crate::parser::grammar::Stmt;
+use crate::parser::scanner::{Scanner, TokenWithSpan};
 use crate::parser::{FileUtf8Reader, Parser};
 
+// Exit codes, from BSD's sysexits.h like jlox.
+const EXIT_USAGE: i32 = 64;
+const EXIT_DATA_ERROR: i32 = 65;
+const EXIT_NO_INPUT: i32 = 66;
+const EXIT_SOFTWARE: i32 = 70;
+
 fn read_file(file: File) -> Result<(), Box<dyn Error>> {
     println!("Reading file one char at a time:");
     let reader = FileUtf8Reader::new(file);
 
     for c in reader {
-        println!("'{c}'");
+        match c {
+            Ok(c) => println!("'{c}'"),
+            Err(error) => println!("{error}"),
+        }
     }
 
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    println!("Hello, world from the parser!");
+    // The main thread's stack is too small for deep recursion in Lox,
+    // so everything runs on a thread with a stack of known size.
+    let run = thread::Builder::new().stack_size(STACK_SIZE).spawn(run)?;
+
+    // A panic has already been printed by the thread.
+    run.join()
+        .unwrap_or_else(|_| process::exit(EXIT_SOFTWARE))?;
+
+    Ok(())
+}
+
+fn run() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let current_dir = env::current_dir().unwrap();
-    let current_dir_str = current_dir.into_os_string().into_string().unwrap();
-    println!("Current directory is: {current_dir_str}");
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
 
-    let args: Vec<String> = env::args().collect();
-    println!("Command line args are: {}\n", format!("{:?}", args));
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    if options.verbose {
+        let current_dir = env::current_dir()?;
+        eprintln!("Current directory is: {}", current_dir.display());
+        eprintln!("Command line args are: {:?}\n", args);
+    }
 
     if cfg!(feature = "test_file_reader") {
-        // Test our FileUtf8Reader.
-        let file = File::open(&args[1]).unwrap();
-        read_file(file).unwrap();
+        // Test our FileUtf8Reader. Stdin can only be read once,
+        // so we leave it for the command.
+        if let Some(path) = options.command.path().filter(|path| *path != "-") {
+            read_file(open_file(path)).unwrap();
+        }
     }
 
-    const PARSER_TEST_FILE: &str = "../programs/basic_parser_test.lox";
+    match &options.command {
+        Command::Help => println!("{USAGE}"),
+
+        Command::Explain(code) => match ErrorCode::lookup(code) {
+            Some(error_code) => print!(
+                "{}: {}\n\n{}",
+                error_code.code, error_code.title, error_code.explanation
+            ),
 
-    // Test our scanner.
+            None => {
+                eprintln!("'{code}' is not an error code.");
+                process::exit(EXIT_USAGE);
+            }
+        },
+        Command::Repl => repl::run_prompt(options.script_args)?,
 
-    let file = File::open(PARSER_TEST_FILE).unwrap();
-    let mut scanner = Scanner::new(file);
+        Command::Tokens(path) => {
+            let script = Script::open(path, options.error_format);
+            let (tokens, had_scan_error) = scan_script(&script);
+            for token in &tokens {
+                println!(
+                    "{}:{}  {:?}",
+                    token.span.line, token.span.column, token.token
+                );
+            }
 
-    scanner.scan_tokens();
-    let tokens = scanner.tokens;
+            if had_scan_error {
+                script.exit(EXIT_DATA_ERROR);
+            }
+        }
 
-    println!("Recognized tokens are: {:?}", tokens);
+        Command::Ast(path) => {
+            for statement in
+                parse_script(&Script::open(path, options.error_format), options.verbose)
+            {
+                println!("{}", statement.pretty_print());
+            }
+        }
 
-    // Use scanned tokens to test our parser.
+        Command::Check(path) => {
+            check_script(&Script::open(path, options.error_format), options.verbose);
+        }
 
-    let parser = Parser::new(tokens);
-    let repr = parser.pretty_print();
+        Command::Run(path) => {
+            let script = Script::open(path, options.error_format);
+            let statements = check_script(&script, options.verbose);
 
-    println!("AST: {repr}");
+            let mut interpreter = Interpreter::with_script_args(options.script_args);
+            if let Err(error) = interpreter.interpret(&statements) {
+                script.report([&error]);
+                script.exit(EXIT_SOFTWARE);
+            }
+        }
+    }
 
     Ok(())
 }
+
+fn open_file(path: &str) -> File {
+    match File::open(path) {
+        Ok(file) => file,
+
+        Err(error) => {
+            eprintln!("Could not open '{path}': {error}");
+            process::exit(EXIT_NO_INPUT);
+        }
+    }
+}
+
+// A script named on the command line. Source read from stdin is
+// kept, since it can't be read again to show in error messages.
+struct Script {
+    path: String,
+    stdin_source: Option<Vec<u8>>,
+    error_format: ErrorFormat,
+    // Code of the first error reported, if any.
+    first_code: Cell<Option<&'static str>>,
+}
+
+impl Script {
+    fn open(path: &str, error_format: ErrorFormat) -> Script {
+        let stdin_source = if path == "-" {
+            // Read bytes rather than a string, so the scanner can
+            // report any invalid utf-8 where it is.
+            let mut source = Vec::new();
+            if let Err(error) = io::stdin().read_to_end(&mut source) {
+                eprintln!("Could not read stdin: {error}");
+                process::exit(EXIT_NO_INPUT);
+            }
+            Some(source)
+        } else {
+            None
+        };
+
+        Script {
+            path: path.to_owned(),
+            stdin_source,
+            error_format,
+            first_code: Cell::new(None),
+        }
+    }
+
+    fn scanner(&self) -> Scanner {
+        match &self.stdin_source {
+            Some(source) => Scanner::from_reader(Cursor::new(source.clone())),
+            None => Scanner::new(open_file(&self.path)),
+        }
+    }
+
+    // Prints errors with the lines of the script they point at.
+    fn report<'a, E: 'a>(&self, errors: impl IntoIterator<Item = &'a E>)
+    where
+        Diagnostic: From<&'a E>,
+    {
+        let source_map = match &self.stdin_source {
+            Some(source) => SourceMap::new("<stdin>", source.clone()),
+
+            // If the file has gone, we can still show where the errors were.
+            None => {
+                SourceMap::load(&self.path).unwrap_or_else(|_| SourceMap::new(&self.path, vec![]))
+            }
+        };
+
+        for error in errors {
+            let diagnostic = Diagnostic::from(error);
+            if self.first_code.get().is_none() {
+                self.first_code.set(diagnostic.code);
+            }
+
+            match self.error_format {
+                ErrorFormat::Human => eprintln!("{}", diagnostic.render(&source_map)),
+                ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(&source_map)),
+            }
+        }
+    }
+
+    // Exits after reporting errors, pointing at how to learn more about them.
+    fn exit(&self, status: i32) -> ! {
+        if let (Some(code), ErrorFormat::Human) = (self.first_code.get(), self.error_format) {
+            eprintln!("For more information about an error, try `interpreter explain {code}`.");
+        }
+
+        process::exit(status);
+    }
+}
+
+// Scans a script, reporting any lexical errors. Returns the
+// tokens and whether there were errors, so the parser can
+// go on to report any errors it finds too.
+fn scan_script(script: &Script) -> (Vec<TokenWithSpan>, bool) {
+    let mut scanner = script.scanner();
+
+    let had_scan_error = !scanner.scan_tokens().is_empty();
+    script.report(&scanner.errors);
+
+    (scanner.tokens, had_scan_error)
+}
+
+// Scans and parses a script, exiting if there are any errors.
+fn parse_script(script: &Script, verbose: bool) -> Vec<Stmt> {
+    let (tokens, had_scan_error) = scan_script(script);
+
+    if verbose {
+        let bare_tokens: Vec<_> = tokens.iter().map(|token| &token.token).collect();
+        eprintln!("Recognized tokens are: {:?}", bare_tokens);
+    }
+
+    let mut parser = Parser::new(tokens);
+    match parser.parse_program() {
+        Ok(_) if had_scan_error => script.exit(EXIT_DATA_ERROR),
+        Ok(statements) => statements,
+
+        Err(errors) => {
+            script.report(&errors);
+            script.exit(EXIT_DATA_ERROR);
+        }
+    }
+}
+
+// Parses and resolves a script, exiting if there are any errors.
+// What's returned is ready to run.
+fn check_script(script: &Script, verbose: bool) -> Vec<Stmt> {
+    let statements = parse_script(script, verbose);
+
+    if verbose {
+        eprintln!("AST:");
+        for statement in &statements {
+            eprintln!("  {}", statement.pretty_print());
+        }
+        eprintln!();
+    }
+
+    if let Err(errors) = Resolver::new().resolve(&statements) {
+        script.report(&errors);
+        script.exit(EXIT_DATA_ERROR);
+    }
+
+    statements
+}
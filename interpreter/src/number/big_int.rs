@@ -0,0 +1,434 @@
+/// Integers of any size, which int arithmetic moves to when its
+/// result won't fit in an i64.
+///
+///
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+const LIMB_BITS: u32 = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    // The magnitude in base 2^32, least significant limb first,
+    // with no high zero limbs. Zero has no limbs at all.
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        trim(&mut limbs);
+        // There's no negative zero.
+        let negative = negative && !limbs.is_empty();
+
+        BigInt { negative, limbs }
+    }
+
+    /// Parses unsigned digits in the radix. The caller
+    /// has already checked they're all valid.
+    pub fn from_digits(digits: &str, radix: u32) -> Self {
+        let mut limbs = Vec::new();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).expect("Digits were validated.");
+            mul_add_small(&mut limbs, radix, digit);
+        }
+
+        BigInt::from_parts(false, limbs)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|limb| limb & 1 == 1)
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.limbs.clone())
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &limb| (acc << LIMB_BITS) | limb as u64);
+
+        if self.negative {
+            // i64::MIN's magnitude is one more than i64::MAX.
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Going through the decimal digits rounds correctly,
+        // and gives infinity when it's too large.
+        self.to_string().parse().unwrap()
+    }
+
+    /// Multiplies by 10 to the power of the exponent.
+    pub fn mul_pow10(&self, exponent: u32) -> BigInt {
+        let mut limbs = self.limbs.clone();
+        for _ in 0..exponent {
+            mul_add_small(&mut limbs, 10, 0);
+        }
+
+        BigInt::from_parts(self.negative, limbs)
+    }
+
+    /// Division truncating toward zero, with the remainder taking the
+    /// sign of the dividend, the same as for i64. None when dividing
+    /// by zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &divisor.limbs);
+        Some((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    // Adds a number with the given sign and magnitude.
+    fn add_signed(&self, negative: bool, limbs: &[u32]) -> BigInt {
+        if self.negative == negative {
+            return BigInt::from_parts(negative, add_magnitude(&self.limbs, limbs));
+        }
+
+        // The signs differ, so subtract the smaller magnitude
+        // from the larger, and keep the larger one's sign.
+        match cmp_magnitude(&self.limbs, limbs) {
+            Ordering::Less => BigInt::from_parts(negative, sub_magnitude(limbs, &self.limbs)),
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.limbs, limbs)),
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(number: i64) -> Self {
+        let magnitude = number.unsigned_abs();
+        BigInt::from_parts(
+            number < 0,
+            vec![magnitude as u32, (magnitude >> LIMB_BITS) as u32],
+        )
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        self.add_signed(other.negative, &other.limbs)
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self.add_signed(!other.negative, &other.limbs)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.limbs, &other.limbs),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, lowest first.
+        let mut chunks = Vec::new();
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            chunks.push(div_rem_small(&mut limbs, 1_000_000_000));
+            trim(&mut limbs);
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// -------------------------------
+// Arithmetic on raw magnitudes.
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+// Both must be trimmed, so the longer one is larger.
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let limb_sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(limb_sum as u32);
+        carry = limb_sum >> LIMB_BITS;
+    }
+    sum.push(carry as u32);
+
+    sum
+}
+
+// The caller makes sure a >= b.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut limb_difference = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if limb_difference < 0 {
+            limb_difference += 1 << LIMB_BITS;
+            borrow = 1;
+        }
+        difference.push(limb_difference as u32);
+    }
+
+    difference
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        // At most (2^32 - 1)^2 + 2 * (2^32 - 1), which fits in a u64.
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let limb_product = x as u64 * y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = limb_product as u32;
+            carry = limb_product >> LIMB_BITS;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+
+    product
+}
+
+fn mul_add_small(limbs: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in limbs.iter_mut() {
+        let value = *limb as u64 * factor as u64 + carry;
+        *limb = value as u32;
+        carry = value >> LIMB_BITS;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+// Divides in place, returning the remainder. Leaves the limbs untrimmed.
+fn div_rem_small(limbs: &mut [u32], divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let value = (remainder << LIMB_BITS) | *limb as u64;
+        *limb = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+
+    remainder as u32
+}
+
+// The divisor isn't zero.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let remainder = div_rem_small(&mut quotient, *divisor);
+        return (quotient, vec![remainder]);
+    }
+
+    // Plain binary long division, one bit of the dividend at a time.
+    // It's slow for huge numbers, but simple.
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = Vec::new();
+    for bit in (0..a.len() * LIMB_BITS as usize).rev() {
+        let (limb, shift) = (bit / LIMB_BITS as usize, bit % LIMB_BITS as usize);
+        shift_left_one(&mut remainder, (a[limb] >> shift) & 1);
+
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            trim(&mut remainder);
+            quotient[limb] |= 1 << shift;
+        }
+    }
+
+    (quotient, remainder)
+}
+
+// Shifts a trimmed magnitude left a bit, bringing in the low bit.
+fn shift_left_one(limbs: &mut Vec<u32>, low_bit: u32) {
+    let mut carry = low_bit;
+    for limb in limbs.iter_mut() {
+        let high_bit = *limb >> (LIMB_BITS - 1);
+        *limb = (*limb << 1) | carry;
+        carry = high_bit;
+    }
+    if carry > 0 {
+        limbs.push(carry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parses decimal digits with an optional leading '-'.
+    fn int(text: &str) -> BigInt {
+        match text.strip_prefix('-') {
+            Some(digits) => -&BigInt::from_digits(digits, 10),
+            None => BigInt::from_digits(text, 10),
+        }
+    }
+
+    #[test]
+    fn adds_and_subtracts_across_signs_and_limbs() {
+        let limb_max = int("4294967295");
+        let one = BigInt::from(1);
+
+        assert_eq!(&limb_max + &one, int("4294967296"));
+        assert_eq!(&int("4294967296") - &one, limb_max);
+        assert_eq!(&int("-4294967296") + &one, int("-4294967295"));
+        assert_eq!(&one - &int("4294967296"), int("-4294967295"));
+        assert_eq!(&int("-1") - &limb_max, int("-4294967296"));
+        assert_eq!(&BigInt::from(i64::MAX) + &one, int("9223372036854775808"));
+        assert_eq!(&limb_max - &limb_max, BigInt::from(0));
+        assert!(!(&int("-5") + &int("5")).is_negative());
+    }
+
+    #[test]
+    fn multiplies_across_signs_and_limbs() {
+        let u64_max = int("18446744073709551615");
+
+        assert_eq!(
+            &u64_max * &u64_max,
+            int("340282366920938463426481119284349108225")
+        );
+        assert_eq!(&u64_max * &int("-2"), int("-36893488147419103230"));
+        assert_eq!(&int("-3") * &int("-4"), BigInt::from(12));
+        assert!(!(&int("-3") * &BigInt::from(0)).is_negative());
+    }
+
+    #[test]
+    fn divides_truncating_toward_zero() {
+        for (dividend, divisor, quotient, remainder) in [
+            (7, 2, 3, 1),
+            (-7, 2, -3, -1),
+            (7, -2, -3, 1),
+            (-7, -2, 3, -1),
+        ] {
+            assert_eq!(
+                BigInt::from(dividend).div_rem(&BigInt::from(divisor)),
+                Some((BigInt::from(quotient), BigInt::from(remainder)))
+            );
+        }
+    }
+
+    #[test]
+    fn divides_by_multi_limb_divisors() {
+        // (2^96 + 5) / (2^64 + 1)
+        let (quotient, remainder) = int("79228162514264337593543950341")
+            .div_rem(&int("18446744073709551617"))
+            .unwrap();
+
+        assert_eq!(quotient, int("4294967295"));
+        assert_eq!(remainder, int("18446744069414584326"));
+
+        let (quotient, remainder) = int("-79228162514264337593543950341")
+            .div_rem(&int("18446744073709551617"))
+            .unwrap();
+
+        assert_eq!(quotient, int("-4294967295"));
+        assert_eq!(remainder, int("-18446744069414584326"));
+    }
+
+    #[test]
+    fn refuses_to_divide_by_zero() {
+        assert_eq!(BigInt::from(1).div_rem(&BigInt::from(0)), None);
+    }
+
+    #[test]
+    fn converts_to_i64_only_within_range() {
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(int("9223372036854775808").to_i64(), None);
+        assert_eq!(int("-9223372036854775809").to_i64(), None);
+        assert_eq!(int("-18446744073709551616").to_i64(), None);
+    }
+
+    #[test]
+    fn displays_decimal_digits() {
+        assert_eq!(BigInt::from(0).to_string(), "0");
+        assert_eq!(BigInt::from(-42).to_string(), "-42");
+        // The lower chunks of nine digits keep their zeros.
+        assert_eq!(int("1000000000").to_string(), "1000000000");
+        assert_eq!(
+            int("-100000000000000000000000000001").to_string(),
+            "-100000000000000000000000000001"
+        );
+        assert_eq!(BigInt::from(i64::MIN).to_string(), i64::MIN.to_string());
+    }
+}
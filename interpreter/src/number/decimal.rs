@@ -0,0 +1,333 @@
+/// Exact decimal numbers, written with a `d` suffix like `0.1d`, for
+/// sums of money and the like that binary floats can only approximate.
+///
+///
+use crate::number::BigInt;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// Fractional digits kept when a quotient doesn't terminate, like 1d / 3,
+// or when a product would have more, like 0.1 multiplied by itself 30 times.
+const ROUNDED_DIGITS: u32 = 28;
+
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    // The value is mantissa / 10^scale. The scale is kept from the
+    // literal, so 1.50d prints as 1.50, but doesn't affect equality.
+    mantissa: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Parses `digits[.digits]`, which the caller has already checked.
+    pub fn parse(text: &str) -> Self {
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        Decimal {
+            mantissa: BigInt::from_digits(&format!("{integer}{fraction}"), 10),
+            scale: fraction.len() as u32,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Going through the digits rounds correctly.
+        self.to_string().parse().unwrap()
+    }
+
+    // Both mantissas, scaled up to the larger of the two scales.
+    fn align(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (
+            self.mantissa.mul_pow10(scale - self.scale),
+            other.mantissa.mul_pow10(scale - other.scale),
+            scale,
+        )
+    }
+
+    /// Exact when the quotient terminates within `ROUNDED_DIGITS`
+    /// fractional digits, and otherwise rounded half to even, the way
+    /// banks round. None when dividing by zero.
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        let scale = ROUNDED_DIGITS.max(self.scale).max(other.scale);
+        // Scale the dividend so the integer quotient has `scale` digits
+        // after the point: (a / 10^sa) / (b / 10^sb) * 10^scale.
+        let dividend = self.mantissa.mul_pow10(scale + other.scale - self.scale);
+
+        Some(
+            Decimal {
+                mantissa: div_round(&dividend, &other.mantissa)?,
+                scale,
+            }
+            .trim_zeros(),
+        )
+    }
+
+    /// The remainder of truncating division, with the sign of the
+    /// dividend. None when dividing by zero.
+    pub fn checked_rem(&self, other: &Decimal) -> Option<Decimal> {
+        let (left, right, scale) = self.align(other);
+        let (_, remainder) = left.div_rem(&right)?;
+
+        Some(Decimal {
+            mantissa: remainder,
+            scale,
+        })
+    }
+
+    // Drops zeros from the end of the fraction.
+    fn trim_zeros(mut self) -> Decimal {
+        let ten = BigInt::from(10);
+        while self.scale > 0 {
+            let (quotient, remainder) = self.mantissa.div_rem(&ten).unwrap();
+            if !remainder.is_zero() {
+                break;
+            }
+            self.mantissa = quotient;
+            self.scale -= 1;
+        }
+
+        self
+    }
+}
+
+// Integer division rounding half to even. None when dividing by zero.
+fn div_round(dividend: &BigInt, divisor: &BigInt) -> Option<BigInt> {
+    let (quotient, remainder) = dividend.div_rem(divisor)?;
+
+    let twice_remainder = (&remainder + &remainder).abs();
+    let round_away = match twice_remainder.cmp(&divisor.abs()) {
+        Ordering::Greater => true,
+        Ordering::Equal => quotient.is_odd(),
+        Ordering::Less => false,
+    };
+    if !round_away {
+        return Some(quotient);
+    }
+
+    let one = BigInt::from(1);
+    if dividend.is_negative() != divisor.is_negative() {
+        Some(&quotient - &one)
+    } else {
+        Some(&quotient + &one)
+    }
+}
+
+impl From<BigInt> for Decimal {
+    fn from(mantissa: BigInt) -> Self {
+        Decimal { mantissa, scale: 0 }
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        let (left, right, scale) = self.align(other);
+        Decimal {
+            mantissa: &left + &right,
+            scale,
+        }
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        let (left, right, scale) = self.align(other);
+        Decimal {
+            mantissa: &left - &right,
+            scale,
+        }
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    // Exact unless the product has more than `ROUNDED_DIGITS` fractional
+    // digits, and more than either factor, so repeated multiplication,
+    // like compounding interest, doesn't grow the digits without bound.
+    fn mul(self, other: &Decimal) -> Decimal {
+        let mantissa = &self.mantissa * &other.mantissa;
+        let scale = self.scale + other.scale;
+
+        let rounded_scale = ROUNDED_DIGITS.max(self.scale).max(other.scale);
+        if scale <= rounded_scale {
+            return Decimal { mantissa, scale };
+        }
+
+        let divisor = BigInt::from(1).mul_pow10(scale - rounded_scale);
+        Decimal {
+            mantissa: div_round(&mantissa, &divisor).unwrap(),
+            scale: rounded_scale,
+        }
+        .trim_zeros()
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal {
+            mantissa: -&self.mantissa,
+            scale: self.scale,
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (left, right, _) = self.align(other);
+        left.cmp(&right)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.abs().to_string();
+        // Pad with zeros so there's at least one digit before the point.
+        let digits = format!("{digits:0>width$}", width = self.scale as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+
+        if self.mantissa.is_negative() {
+            write!(f, "-")?;
+        }
+        if fraction.is_empty() {
+            write!(f, "{integer}")
+        } else {
+            write!(f, "{integer}.{fraction}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parses a decimal with an optional leading '-'.
+    fn dec(text: &str) -> Decimal {
+        match text.strip_prefix('-') {
+            Some(digits) => -&Decimal::parse(digits),
+            None => Decimal::parse(text),
+        }
+    }
+
+    #[test]
+    fn aligns_scales_for_arithmetic() {
+        assert_eq!((&dec("1.5") + &dec("0.25")).to_string(), "1.75");
+        assert_eq!((&dec("1") - &dec("0.001")).to_string(), "0.999");
+        assert_eq!((&dec("1.50") - &dec("1.5")).to_string(), "0.00");
+        assert_eq!((&dec("-0.5") + &dec("0.25")).to_string(), "-0.25");
+        assert_eq!((&dec("1.5") * &dec("-0.20")).to_string(), "-0.300");
+    }
+
+    #[test]
+    fn compares_values_regardless_of_scale() {
+        assert_eq!(dec("1.50"), dec("1.5"));
+        assert!(dec("0.10") < dec("0.2"));
+        assert!(dec("-1.1") < dec("-1.05"));
+    }
+
+    #[test]
+    fn displays_leading_zeros_and_scale() {
+        assert_eq!(dec("0.05").to_string(), "0.05");
+        assert_eq!(dec("-0.05").to_string(), "-0.05");
+        assert_eq!(dec("12.340").to_string(), "12.340");
+        assert_eq!(dec("7").to_string(), "7");
+    }
+
+    #[test]
+    fn trims_trailing_zeros_from_quotients() {
+        assert_eq!(dec("1").checked_div(&dec("4")).unwrap().to_string(), "0.25");
+        assert_eq!(dec("10.0").checked_div(&dec("2")).unwrap().to_string(), "5");
+        assert_eq!(dec("-9").checked_div(&dec("3")).unwrap().to_string(), "-3");
+    }
+
+    #[test]
+    fn rounds_quotients_that_dont_terminate() {
+        assert_eq!(
+            dec("2").checked_div(&dec("3")).unwrap().to_string(),
+            "0.6666666666666666666666666667"
+        );
+        assert_eq!(
+            dec("-1").checked_div(&dec("3")).unwrap().to_string(),
+            "-0.3333333333333333333333333333"
+        );
+        assert_eq!(
+            dec("2").checked_div(&dec("-3")).unwrap().to_string(),
+            "-0.6666666666666666666666666667"
+        );
+    }
+
+    #[test]
+    fn rounds_ties_to_even() {
+        let tiny = |digit: &str| dec(&format!("0.{}{digit}", "0".repeat(27)));
+
+        // 0.5 and 1.5 in the last place round to 0 and 2.
+        assert_eq!(tiny("1").checked_div(&dec("2")).unwrap(), dec("0"));
+        assert_eq!(tiny("3").checked_div(&dec("2")).unwrap(), tiny("2"));
+        assert_eq!((-&tiny("3")).checked_div(&dec("2")).unwrap(), -&tiny("2"));
+    }
+
+    #[test]
+    fn takes_the_remainder_sign_from_the_dividend() {
+        assert_eq!(
+            dec("7.5").checked_rem(&dec("2")).unwrap().to_string(),
+            "1.5"
+        );
+        assert_eq!(
+            dec("-7.5").checked_rem(&dec("2")).unwrap().to_string(),
+            "-1.5"
+        );
+        assert_eq!(
+            dec("7.5").checked_rem(&dec("-2")).unwrap().to_string(),
+            "1.5"
+        );
+        assert_eq!(
+            dec("-7.5").checked_rem(&dec("-2")).unwrap().to_string(),
+            "-1.5"
+        );
+    }
+
+    #[test]
+    fn refuses_to_divide_by_zero() {
+        assert!(dec("1").checked_div(&dec("0.0")).is_none());
+        assert!(dec("1").checked_rem(&dec("0")).is_none());
+    }
+
+    #[test]
+    fn rounds_products_with_too_many_digits() {
+        let mut power = dec("0.5");
+        for _ in 0..29 {
+            power = &power * &dec("0.5");
+        }
+        // 0.5^30 is 0.000000000931322574615478515625 exactly.
+        assert_eq!(power.to_string(), "0.0000000009313225746154785156");
+
+        // Factors that already have more digits keep them.
+        let long = dec(&format!("0.{}1", "0".repeat(29)));
+        assert_eq!(
+            (&long * &dec("2")).to_string(),
+            format!("0.{}2", "0".repeat(29))
+        );
+        // Half a unit in the last place rounds to even.
+        assert_eq!((&long * &dec("0.5")).to_string(), "0");
+    }
+}
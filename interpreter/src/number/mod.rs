@@ -0,0 +1,5 @@
+pub mod big_int;
+pub mod decimal;
+
+pub use crate::number::big_int::*;
+pub use crate::number::decimal::*;
@@ -0,0 +1,184 @@
+/// Errors found while reading, scanning and parsing source.
+///
+///
+use crate::parser::scanner::Span;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// --------------
+// Decode errors.
+
+#[derive(Debug)]
+pub enum DecodeErrorKind {
+    // Bytes that don't form a utf-8 char.
+    InvalidSequence(Vec<u8>),
+    // The underlying reader failed.
+    Io(io::Error),
+}
+
+#[derive(Debug)]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    // Offset in the source of the first offending byte.
+    pub byte_offset: usize,
+}
+
+impl DecodeError {
+    // Number of source bytes the error covers.
+    pub fn byte_len(&self) -> usize {
+        match &self.kind {
+            DecodeErrorKind::InvalidSequence(bytes) => bytes.len(),
+            DecodeErrorKind::Io(_) => 0,
+        }
+    }
+}
+
+/// Formats bytes like `\xC3\x28`.
+pub fn escape_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("\\x{byte:02X}")).collect()
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DecodeErrorKind::InvalidSequence(bytes) => write!(
+                f,
+                "Invalid utf-8 sequence '{}' at byte {}.",
+                escape_bytes(bytes),
+                self.byte_offset
+            ),
+            DecodeErrorKind::Io(error) => {
+                write!(f, "Could not read byte {}: {error}", self.byte_offset)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            DecodeErrorKind::Io(error) => Some(error),
+            DecodeErrorKind::InvalidSequence(_) => None,
+        }
+    }
+}
+
+// --------------
+// Scanner errors.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanErrorKind {
+    UnterminatedString,
+    InvalidNumber,
+    // A char that can't start any token.
+    UnexpectedCharacter,
+    // Source bytes that aren't utf-8.
+    InvalidUtf8,
+    // The source couldn't be read.
+    Io,
+    // A '\' in a string followed by a char that isn't an escape.
+    UnknownEscape,
+    // A \u escape that isn't of the form \u{XXXX}.
+    InvalidUnicodeEscape,
+    // A well-formed \u escape for a number that isn't a char.
+    InvalidCodePoint,
+    // A number literal too large for its type.
+    NumberOverflow,
+    // A `/*` comment with no matching `*/`.
+    UnterminatedComment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub kind: ScanErrorKind,
+    pub span: Span,
+    // The offending source text.
+    pub text: String,
+}
+
+impl ScanError {
+    pub fn message(&self) -> String {
+        match self.kind {
+            ScanErrorKind::UnterminatedString => String::from("Unterminated string literal."),
+            ScanErrorKind::UnterminatedComment => String::from("Unterminated block comment."),
+            ScanErrorKind::InvalidNumber => format!("Invalid numeric literal '{}'.", self.text),
+            ScanErrorKind::UnexpectedCharacter => {
+                format!("Unexpected character '{}'.", self.text)
+            }
+            ScanErrorKind::InvalidUtf8 => format!("Invalid utf-8 sequence '{}'.", self.text),
+            ScanErrorKind::Io => format!("Could not read source: {}", self.text),
+            ScanErrorKind::UnknownEscape => format!("Unknown escape sequence '{}'.", self.text),
+            ScanErrorKind::InvalidUnicodeEscape => {
+                format!("Invalid unicode escape '{}'.", self.text)
+            }
+            ScanErrorKind::InvalidCodePoint => {
+                format!("'{}' is not a valid unicode code point.", self.text)
+            }
+            ScanErrorKind::NumberOverflow => {
+                format!("Number literal '{}' is too large.", self.text)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Scan error: {}",
+            self.span.line,
+            self.span.column,
+            self.message()
+        )
+    }
+}
+
+impl Error for ScanError {}
+
+// --------------
+// Parser errors.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    // A token that cannot start an expression.
+    ExpectedExpression,
+    // A specific token, like a closing ')', is missing.
+    ExpectedToken,
+    // The left side of an '=' isn't something we can assign to.
+    InvalidAssignmentTarget,
+    // More than 255 arguments or parameters.
+    TooManyArguments,
+    // Statements or expressions nested past the parser's limit.
+    TooDeeplyNested,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Span, message: &str) -> Self {
+        ParseError {
+            kind,
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Parse error: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
@@ -1,5 +1,7 @@
+use crate::parser::{DecodeError, DecodeErrorKind};
+
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, ErrorKind, Read};
 use std::str;
 
 /// This just needs to be long enough to
@@ -9,40 +11,114 @@ const BUFFER_SIZE: usize = 12;
 /// Wraps BufReader<File> and allows us to read a file
 /// one utf-8 character at a time, without loading the
 /// entire file into memory at once.
-pub struct FileUtf8Reader {
-    reader: BufReader<File>,
+///
+/// Any other `Read` source, like stdin or a byte slice,
+/// can be read the same way with `from_reader`.
+///
+/// Bytes that aren't valid utf-8 are reported as errors
+/// and skipped, so reading can carry on after them. An
+/// I/O error is reported once and ends the iterator.
+pub struct FileUtf8Reader<R: Read = File> {
+    reader: BufReader<R>,
+    // Number of bytes read so far, which is
+    // also the offset of the next char.
+    byte_offset: usize,
+    // Set once reading fails, so we stop.
+    failed: bool,
+}
+
+impl FileUtf8Reader<File> {
+    pub fn new(file: File) -> FileUtf8Reader<File> {
+        Self::from_reader(file)
+    }
 }
 
-impl FileUtf8Reader {
-    pub fn new(file: File) -> FileUtf8Reader {
+impl<R: Read> FileUtf8Reader<R> {
+    pub fn from_reader(reader: R) -> FileUtf8Reader<R> {
         FileUtf8Reader {
-            reader: BufReader::with_capacity(BUFFER_SIZE, file),
+            reader: BufReader::with_capacity(BUFFER_SIZE, reader),
+            byte_offset: 0,
+            failed: false,
+        }
+    }
+
+    // Next byte of the source, without consuming it.
+    fn peek_byte(&mut self) -> Result<Option<u8>, DecodeError> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buffer) => return Ok(buffer.first().copied()),
+
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.failed = true;
+                    return Err(DecodeError {
+                        kind: DecodeErrorKind::Io(error),
+                        byte_offset: self.byte_offset,
+                    });
+                }
+            }
+        }
+    }
+
+    fn consume_byte(&mut self) {
+        self.reader.consume(1);
+        self.byte_offset += 1;
+    }
+
+    fn invalid_sequence(&self, bytes: &[u8]) -> DecodeError {
+        DecodeError {
+            kind: DecodeErrorKind::InvalidSequence(bytes.to_vec()),
+            byte_offset: self.byte_offset - bytes.len(),
         }
     }
 }
 
-impl Iterator for FileUtf8Reader {
-    type Item = char;
+impl<R: Read> Iterator for FileUtf8Reader<R> {
+    type Item = Result<char, DecodeError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // The idea to use fill_buf for this comes from:
         //  https://stackoverflow.com/questions/37079342/
 
-        let buffer = self.reader.fill_buf().unwrap();
-
-        if buffer.is_empty() {
+        if self.failed {
             return None;
         }
 
-        let char_len = UTF8_CHAR_WIDTH[buffer[0] as usize];
-        let char_bytes = &buffer[0..char_len];
+        let first = match self.peek_byte() {
+            Ok(Some(byte)) => byte,
+            Ok(None) => return None,
+            Err(error) => return Some(Err(error)),
+        };
+        self.consume_byte();
+
+        // A byte that can't start a char, like a stray continuation byte.
+        let char_len = UTF8_CHAR_WIDTH[first as usize];
+        if char_len == 0 {
+            return Some(Err(self.invalid_sequence(&[first])));
+        }
 
-        let char_str = str::from_utf8(char_bytes).unwrap();
-        let the_char = char_str.chars().nth(0).unwrap();
+        // We take the rest of the char's bytes one at a time, since
+        // they may not all be in the buffer until it's refilled.
+        let mut char_bytes = [first, 0, 0, 0];
+        for i in 1..char_len {
+            match self.peek_byte() {
+                // Leave a byte that isn't a continuation, since
+                // it may start the next char.
+                Ok(Some(byte)) if byte & 0xC0 == 0x80 => {
+                    char_bytes[i] = byte;
+                    self.consume_byte();
+                }
+                Ok(_) => return Some(Err(self.invalid_sequence(&char_bytes[..i]))),
 
-        self.reader.consume(char_len);
+                Err(error) => return Some(Err(error)),
+            }
+        }
 
-        Some(the_char)
+        // This still rejects overlong encodings and surrogates.
+        match str::from_utf8(&char_bytes[..char_len]) {
+            Ok(char_str) => char_str.chars().next().map(Ok),
+            Err(_) => Some(Err(self.invalid_sequence(&char_bytes[..char_len]))),
+        }
     }
 }
 
@@ -67,3 +143,124 @@ const UTF8_CHAR_WIDTH: &[usize; 256] = &[
     3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, // E
     4, 4, 4, 4, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // F
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io;
+
+    // Decoded chars, with errors as their bytes and offset.
+    fn decode(reader: impl Read) -> Vec<Result<char, (Vec<u8>, usize)>> {
+        FileUtf8Reader::from_reader(reader)
+            .map(|result| {
+                result.map_err(|error| match error.kind {
+                    DecodeErrorKind::InvalidSequence(bytes) => (bytes, error.byte_offset),
+                    DecodeErrorKind::Io(io_error) => panic!("Unexpected I/O error: {io_error}"),
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_chars_straddling_a_buffer_refill() {
+        // Shift the chars across every position in the buffer.
+        for padding in 0..BUFFER_SIZE {
+            let source = format!("{}é€😀x", "a".repeat(padding));
+            let expected: Vec<_> = source.chars().map(Ok).collect();
+
+            assert_eq!(decode(source.as_bytes()), expected, "padding {padding}");
+        }
+    }
+
+    #[test]
+    fn reports_a_sequence_truncated_at_the_end() {
+        assert_eq!(
+            decode(&b"a\xE2\x82"[..]),
+            [Ok('a'), Err((vec![0xE2, 0x82], 1))]
+        );
+    }
+
+    #[test]
+    fn keeps_the_byte_that_ends_a_truncated_sequence() {
+        assert_eq!(
+            decode(&b"\xF0\x9F\x98a"[..]),
+            [Err((vec![0xF0, 0x9F, 0x98], 0)), Ok('a')]
+        );
+    }
+
+    #[test]
+    fn reports_lone_continuation_bytes() {
+        assert_eq!(
+            decode(&b"\x80a\xBF"[..]),
+            [Err((vec![0x80], 0)), Ok('a'), Err((vec![0xBF], 2))]
+        );
+    }
+
+    #[test]
+    fn rejects_overlong_encodings() {
+        // 0xC0 and 0xC1 can only start overlong sequences.
+        assert_eq!(
+            decode(&b"\xC0\x80"[..]),
+            [Err((vec![0xC0], 0)), Err((vec![0x80], 1))]
+        );
+        assert_eq!(
+            decode(&b"\xE0\x80\x80"[..]),
+            [Err((vec![0xE0, 0x80, 0x80], 0))]
+        );
+    }
+
+    #[test]
+    fn rejects_surrogates() {
+        assert_eq!(
+            decode(&b"\xED\xA0\x80!"[..]),
+            [Err((vec![0xED, 0xA0, 0x80], 0)), Ok('!')]
+        );
+    }
+
+    // Fails every other read with `Interrupted`.
+    struct Interrupting<'a> {
+        bytes: &'a [u8],
+        interrupt: bool,
+    }
+
+    impl Read for Interrupting<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            self.bytes.read(buffer)
+        }
+    }
+
+    #[test]
+    fn retries_interrupted_reads() {
+        let source = "0123456789😀 and more text";
+        let reader = Interrupting {
+            bytes: source.as_bytes(),
+            interrupt: false,
+        };
+
+        let expected: Vec<_> = source.chars().map(Ok).collect();
+        assert_eq!(decode(reader), expected);
+    }
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn stops_after_a_read_error() {
+        let mut reader = FileUtf8Reader::from_reader(Failing);
+
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(matches!(error.kind, DecodeErrorKind::Io(_)));
+        assert_eq!(error.byte_offset, 0);
+        assert!(reader.next().is_none());
+    }
+}
@@ -1,25 +1,44 @@
-/// Implementing Nystrom's Lox expression grammar.
+/// Implementing Nystrom's Lox grammar.
 ///
 ///
-use crate::parser::Parser;
+use crate::parser::scanner::{NumberLiteral, Span};
+
+use std::cell::Cell;
+use std::rc::Rc;
 
 /// ----------------------------------------------
 /// Grammar definition for Nystrom's Lox language.
 
+// Expressions that refer to variables hold a `depth`, which the
+// resolver fills in with the number of scopes between the use and
+// the declaration. It stays `None` for globals.
+
 #[derive(Debug)]
-// expression → literal | unary | binary | grouping ;
+// expression → literal | unary | binary | grouping | variable | assign
+//            | logical | call | get | set | this | super | interpolation ;
 pub(crate) enum Expression {
-    Literal(Literal),
+    // Literals carry the span of their token.
+    Literal(Literal, Span),
     Unary(Unary),
     Binary(Binary),
     // grouping → "(" expression ")" ;
-    Grouping(Box<Expression>),
+    // The span covers both parentheses.
+    Grouping(Box<Expression>, Span),
+    Variable(Variable),
+    Assign(Assign),
+    Logical(Logical),
+    Call(Call),
+    Get(Get),
+    Set(Set),
+    This(This),
+    Super(Super),
+    Interpolation(Interpolation),
 }
 
 #[derive(Debug)]
 // literal → NUMBER | STRING | "true" | "false" | "nil" ;
 pub(crate) enum Literal {
-    Number(String),
+    Number(NumberLiteral),
     String(String),
     //
     True,
@@ -31,6 +50,8 @@ pub(crate) enum Literal {
 // unary → ( "-" | "!" ) expression ;
 pub(crate) struct Unary {
     pub operator: UnaryOp,
+    // Span of the operator token.
+    pub operator_span: Span,
     pub expr: Box<Expression>,
 }
 
@@ -45,10 +66,104 @@ pub(crate) enum UnaryOp {
 pub(crate) struct Binary {
     pub left: Box<Expression>,
     pub operator: BinaryOp,
+    // Span of the operator token.
+    pub operator_span: Span,
+    pub right: Box<Expression>,
+}
+
+#[derive(Debug)]
+// variable → IDENTIFIER ;
+pub(crate) struct Variable {
+    pub name: String,
+    pub span: Span,
+    pub depth: Cell<Option<usize>>,
+}
+
+#[derive(Debug)]
+// assign → IDENTIFIER "=" expression ;
+pub(crate) struct Assign {
+    pub name: String,
+    pub name_span: Span,
+    pub value: Box<Expression>,
+    pub depth: Cell<Option<usize>>,
+}
+
+#[derive(Debug)]
+// logical → expression ( "and" | "or" ) expression ;
+// These are separate from binary since they short-circuit.
+pub(crate) struct Logical {
+    pub left: Box<Expression>,
+    pub operator: LogicalOp,
     pub right: Box<Expression>,
 }
 
 #[derive(Debug)]
+pub(crate) enum LogicalOp {
+    And,
+    Or,
+}
+
+#[derive(Debug)]
+// call → expression "(" ( expression ( "," expression )* )? ")" ;
+pub(crate) struct Call {
+    pub callee: Box<Expression>,
+    // Span of the closing paren, where we report call errors.
+    pub paren_span: Span,
+    pub arguments: Vec<Expression>,
+}
+
+#[derive(Debug)]
+// get → expression "." IDENTIFIER ;
+pub(crate) struct Get {
+    pub object: Box<Expression>,
+    pub name: String,
+    pub name_span: Span,
+}
+
+#[derive(Debug)]
+// set → expression "." IDENTIFIER "=" expression ;
+pub(crate) struct Set {
+    pub object: Box<Expression>,
+    pub name: String,
+    pub name_span: Span,
+    pub value: Box<Expression>,
+}
+
+#[derive(Debug)]
+// this → "this" ;
+pub(crate) struct This {
+    pub span: Span,
+    pub depth: Cell<Option<usize>>,
+}
+
+#[derive(Debug)]
+// super → "super" "." IDENTIFIER ;
+pub(crate) struct Super {
+    pub keyword_span: Span,
+    pub method: String,
+    pub method_span: Span,
+    pub depth: Cell<Option<usize>>,
+}
+
+#[derive(Debug)]
+// interpolation → INTERPOLATION_START expression
+//                 ( INTERPOLATION_MIDDLE expression )* INTERPOLATION_END ;
+//
+// The tokens hold the text between the interpolated expressions,
+// as in "Hello ${name}, you have ${count + 1} items".
+pub(crate) struct Interpolation {
+    pub parts: Vec<InterpolationPart>,
+    // From the opening quote to the closing one.
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub(crate) enum InterpolationPart {
+    Text(String),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, Copy)]
 pub(crate) enum BinaryOp {
     EqualEqual,
     BangEqual,
@@ -60,22 +175,125 @@ pub(crate) enum BinaryOp {
     Minus,
     Star,
     Slash,
+    Percent,
+}
+
+// --------------------------------------
+// Statements.
+
+// The ';' ending a statement is optional, so a statement
+// simply ends when its expression can't be continued.
+
+// program     → declaration* EOF ;
+// declaration → classDecl | funDecl | varDecl | statement ;
+// statement   → exprStmt | forStmt | ifStmt | printStmt
+//             | returnStmt | whileStmt | block ;
+//
+// A forStmt is desugared by the parser into a while loop,
+// so it doesn't need its own node:
+//
+// forStmt → "for" "(" ( varDecl | exprStmt | ";" )
+//           expression? ";" expression? ")" statement ;
+#[derive(Debug)]
+pub(crate) enum Stmt {
+    // exprStmt → expression ";"? ;
+    Expression(Expression),
+    // printStmt → "print" expression ";"? ;
+    Print(Expression),
+    Var(Var),
+    // block → "{" declaration* "}" ;
+    Block(Vec<Stmt>),
+    If(If),
+    While(While),
+    // Functions are shared with the closures created from them.
+    Function(Rc<Function>),
+    Return(Return),
+    Class(Class),
+}
+
+#[derive(Debug)]
+// varDecl → "var" IDENTIFIER ( "=" expression )? ";"? ;
+pub(crate) struct Var {
+    pub name: String,
+    pub name_span: Span,
+    pub initializer: Option<Expression>,
 }
 
-// -----------------------------
-// Visitor to pretty print ASTs.
+#[derive(Debug)]
+// ifStmt → "if" "(" expression ")" statement ( "else" statement )? ;
+pub(crate) struct If {
+    pub condition: Expression,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
+}
+
+#[derive(Debug)]
+// classDecl → "class" IDENTIFIER ( "<" IDENTIFIER )? "{" function* "}" ;
+pub(crate) struct Class {
+    // Text of the `///` comments just before the declaration.
+    pub doc: Option<String>,
+    pub name: String,
+    pub name_span: Span,
+    pub superclass: Option<Variable>,
+    pub methods: Vec<Rc<Function>>,
+}
+
+#[derive(Debug)]
+// funDecl    → "fun" function ;
+// function   → IDENTIFIER "(" parameters? ")" block ;
+// parameters → IDENTIFIER ( "," IDENTIFIER )* ;
+pub(crate) struct Function {
+    // Text of the `///` comments just before the declaration.
+    pub doc: Option<String>,
+    pub name: String,
+    pub name_span: Span,
+    pub params: Vec<Parameter>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Parameter {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+// returnStmt → "return" expression? ";"? ;
+pub(crate) struct Return {
+    pub keyword_span: Span,
+    pub value: Option<Expression>,
+}
+
+#[derive(Debug)]
+// whileStmt → "while" "(" expression ")" statement ;
+pub(crate) struct While {
+    pub condition: Expression,
+    pub body: Box<Stmt>,
+}
+
+// ---------------------------
+// Visitor for walking ASTs.
 
 // We use the visitor pattern as in Nystrom's ch. 5.
 
-trait Visitor<T> {
+pub(crate) trait Visitor<T> {
     fn visit_literal(&mut self, expression: &Literal) -> T;
     fn visit_unary(&mut self, expression: &Unary) -> T;
     fn visit_binary(&mut self, expression: &Binary) -> T;
+    fn visit_variable(&mut self, expression: &Variable) -> T;
+    fn visit_assign(&mut self, expression: &Assign) -> T;
+    fn visit_logical(&mut self, expression: &Logical) -> T;
+    fn visit_call(&mut self, expression: &Call) -> T;
+    fn visit_get(&mut self, expression: &Get) -> T;
+    fn visit_set(&mut self, expression: &Set) -> T;
+    fn visit_this(&mut self, expression: &This) -> T;
+    fn visit_super(&mut self, expression: &Super) -> T;
+    fn visit_interpolation(&mut self, expression: &Interpolation) -> T;
 
     /// NOTE: Maybe we can find a better way to do this.
     fn visit_grouping(&mut self, expression: &Expression) -> T {
         match expression {
-            Expression::Grouping(inner) => self._visit_grouping_impl(inner),
+            Expression::Grouping(inner, _) => self._visit_grouping_impl(inner),
 
             _ => panic!("Not a grouping expression."),
         }
@@ -86,40 +304,147 @@ trait Visitor<T> {
 // Define accept methods on types.
 
 impl Literal {
-    fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         visitor.visit_literal(self)
     }
 }
 
 impl Unary {
-    fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         visitor.visit_unary(self)
     }
 }
 
 impl Binary {
-    fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         visitor.visit_binary(self)
     }
 }
 
+impl Variable {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_variable(self)
+    }
+}
+
+impl Assign {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_assign(self)
+    }
+}
+
+impl Logical {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_logical(self)
+    }
+}
+
+impl Call {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_call(self)
+    }
+}
+
+impl Get {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_get(self)
+    }
+}
+
+impl Set {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_set(self)
+    }
+}
+
+impl This {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_this(self)
+    }
+}
+
+impl Super {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_super(self)
+    }
+}
+
+impl Interpolation {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+        visitor.visit_interpolation(self)
+    }
+}
+
 impl Expression {
-    fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         match self {
-            Expression::Literal(literal) => literal.accept(visitor),
+            Expression::Literal(literal, _) => literal.accept(visitor),
             Expression::Unary(unary) => unary.accept(visitor),
             Expression::Binary(binary) => binary.accept(visitor),
 
             // NOTE: This is different because we made the Grouping variant
             // directly hold its inner expression, rather than a struct.
-            Expression::Grouping(_) => visitor.visit_grouping(self),
+            Expression::Grouping(..) => visitor.visit_grouping(self),
 
-            // NOTE: Currently unreachable, but it is useful for
-            // debugging when we add new expression types.
-            _ => panic!(
-                "Accept not implemented for this expression variant: {:?}.",
-                self
-            ),
+            Expression::Variable(variable) => variable.accept(visitor),
+            Expression::Assign(assign) => assign.accept(visitor),
+            Expression::Logical(logical) => logical.accept(visitor),
+            Expression::Call(call) => call.accept(visitor),
+            Expression::Get(get) => get.accept(visitor),
+            Expression::Set(set) => set.accept(visitor),
+            Expression::This(this) => this.accept(visitor),
+            Expression::Super(sup) => sup.accept(visitor),
+            Expression::Interpolation(interpolation) => interpolation.accept(visitor),
+        }
+    }
+
+    /// Source range covered by the whole expression.
+    pub(crate) fn span(&self) -> Span {
+        match self {
+            Expression::Literal(_, span) => *span,
+            Expression::Unary(unary) => unary.operator_span.to(unary.expr.span()),
+            Expression::Binary(binary) => binary.left.span().to(binary.right.span()),
+            Expression::Grouping(_, span) => *span,
+            Expression::Variable(variable) => variable.span,
+            Expression::Assign(assign) => assign.name_span.to(assign.value.span()),
+            Expression::Logical(logical) => logical.left.span().to(logical.right.span()),
+            Expression::Call(call) => call.callee.span().to(call.paren_span),
+            Expression::Get(get) => get.object.span().to(get.name_span),
+            Expression::Set(set) => set.object.span().to(set.value.span()),
+            Expression::This(this) => this.span,
+            Expression::Super(sup) => sup.keyword_span.to(sup.method_span),
+            Expression::Interpolation(interpolation) => interpolation.span,
+        }
+    }
+}
+
+// Statements get their own visitor, since visiting
+// them usually produces a different type.
+
+pub(crate) trait StmtVisitor<T> {
+    fn visit_expression_stmt(&mut self, expression: &Expression) -> T;
+    fn visit_print_stmt(&mut self, expression: &Expression) -> T;
+    fn visit_var_stmt(&mut self, stmt: &Var) -> T;
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) -> T;
+    fn visit_if_stmt(&mut self, stmt: &If) -> T;
+    fn visit_while_stmt(&mut self, stmt: &While) -> T;
+    fn visit_function_stmt(&mut self, stmt: &Rc<Function>) -> T;
+    fn visit_return_stmt(&mut self, stmt: &Return) -> T;
+    fn visit_class_stmt(&mut self, stmt: &Class) -> T;
+}
+
+impl Stmt {
+    pub(crate) fn accept<T>(&self, visitor: &mut impl StmtVisitor<T>) -> T {
+        match self {
+            Stmt::Expression(expression) => visitor.visit_expression_stmt(expression),
+            Stmt::Print(expression) => visitor.visit_print_stmt(expression),
+            Stmt::Var(var) => visitor.visit_var_stmt(var),
+            Stmt::Block(statements) => visitor.visit_block_stmt(statements),
+            Stmt::If(stmt) => visitor.visit_if_stmt(stmt),
+            Stmt::While(stmt) => visitor.visit_while_stmt(stmt),
+            Stmt::Function(stmt) => visitor.visit_function_stmt(stmt),
+            Stmt::Return(stmt) => visitor.visit_return_stmt(stmt),
+            Stmt::Class(stmt) => visitor.visit_class_stmt(stmt),
         }
     }
 }
@@ -130,17 +455,26 @@ struct PrettyPrintVisitor;
 
 impl Visitor<String> for PrettyPrintVisitor {
     fn visit_literal(&mut self, expression: &Literal) -> String {
-        let rep: String;
         match expression {
-            Literal::Number(string) => rep = string.clone(),
-            Literal::String(string) => rep = string.clone(),
+            Literal::Number(NumberLiteral::Int(number)) => number.to_string(),
+            Literal::Number(NumberLiteral::BigInt(number)) => number.to_string(),
+            // Written as they'd be scanned: floats with an exponent,
+            // and whole decimals with a 'd'.
+            Literal::Number(NumberLiteral::Float(number)) => format!("{number:e}"),
+            Literal::Number(NumberLiteral::Decimal(number)) => {
+                let text = number.to_string();
+                if text.contains('.') {
+                    text
+                } else {
+                    text + "d"
+                }
+            }
+            Literal::String(string) => string.clone(),
 
-            Literal::True => rep = String::from("true"),
-            Literal::False => rep = String::from("false"),
-            Literal::Nil => rep = String::from("nil"),
+            Literal::True => String::from("true"),
+            Literal::False => String::from("false"),
+            Literal::Nil => String::from("nil"),
         }
-
-        rep
     }
 
     fn visit_unary(&mut self, expression: &Unary) -> String {
@@ -149,7 +483,7 @@ impl Visitor<String> for PrettyPrintVisitor {
             UnaryOp::Minus => String::from("-"),
         };
 
-        format!("({} {})", op, expression.accept(self))
+        format!("({} {})", op, expression.expr.accept(self))
     }
 
     fn visit_binary(&mut self, expression: &Binary) -> String {
@@ -164,6 +498,7 @@ impl Visitor<String> for PrettyPrintVisitor {
             BinaryOp::Minus => String::from("-"),
             BinaryOp::Star => String::from("*"),
             BinaryOp::Slash => String::from("/"),
+            BinaryOp::Percent => String::from("%"),
         };
 
         format!(
@@ -176,17 +511,170 @@ impl Visitor<String> for PrettyPrintVisitor {
     fn _visit_grouping_impl(&mut self, expression: &Expression) -> String {
         format!("(group {})", expression.accept(self))
     }
+
+    fn visit_variable(&mut self, expression: &Variable) -> String {
+        expression.name.clone()
+    }
+
+    fn visit_assign(&mut self, expression: &Assign) -> String {
+        format!("(= {} {})", expression.name, expression.value.accept(self))
+    }
+
+    fn visit_call(&mut self, expression: &Call) -> String {
+        let mut rep = format!("(call {}", expression.callee.accept(self));
+        for argument in &expression.arguments {
+            rep += &format!(" {}", argument.accept(self));
+        }
+        rep += ")";
+
+        rep
+    }
+
+    fn visit_get(&mut self, expression: &Get) -> String {
+        format!("(. {} {})", expression.object.accept(self), expression.name)
+    }
+
+    fn visit_set(&mut self, expression: &Set) -> String {
+        format!(
+            "(= (. {} {}) {})",
+            expression.object.accept(self),
+            expression.name,
+            expression.value.accept(self)
+        )
+    }
+
+    fn visit_this(&mut self, _expression: &This) -> String {
+        String::from("this")
+    }
+
+    fn visit_super(&mut self, expression: &Super) -> String {
+        format!("(super {})", expression.method)
+    }
+
+    fn visit_logical(&mut self, expression: &Logical) -> String {
+        let op = match expression.operator {
+            LogicalOp::And => "and",
+            LogicalOp::Or => "or",
+        };
+
+        format!(
+            "({op} {} {})",
+            expression.left.accept(self),
+            expression.right.accept(self)
+        )
+    }
+
+    // Text parts are quoted, to tell them apart from expressions.
+    fn visit_interpolation(&mut self, expression: &Interpolation) -> String {
+        let mut rep = String::from("(interpolate");
+        for part in &expression.parts {
+            match part {
+                InterpolationPart::Text(text) => rep += &format!(" {text:?}"),
+                InterpolationPart::Expression(expression) => {
+                    rep += &format!(" {}", expression.accept(self))
+                }
+            }
+        }
+        rep += ")";
+
+        rep
+    }
+}
+
+impl StmtVisitor<String> for PrettyPrintVisitor {
+    fn visit_expression_stmt(&mut self, expression: &Expression) -> String {
+        format!("(; {})", expression.accept(self))
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expression) -> String {
+        format!("(print {})", expression.accept(self))
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &Var) -> String {
+        match &stmt.initializer {
+            Some(initializer) => format!("(var {} {})", stmt.name, initializer.accept(self)),
+            None => format!("(var {})", stmt.name),
+        }
+    }
+
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) -> String {
+        let mut rep = String::from("(block");
+        for statement in statements {
+            rep += &format!(" {}", statement.accept(self));
+        }
+        rep += ")";
+
+        rep
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &If) -> String {
+        let condition = stmt.condition.accept(self);
+        let then_branch = stmt.then_branch.accept(self);
+
+        match &stmt.else_branch {
+            Some(else_branch) => format!(
+                "(if {condition} {then_branch} {})",
+                else_branch.accept(self)
+            ),
+            None => format!("(if {condition} {then_branch})"),
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &While) -> String {
+        format!(
+            "(while {} {})",
+            stmt.condition.accept(self),
+            stmt.body.accept(self)
+        )
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<Function>) -> String {
+        let params: Vec<&str> = stmt
+            .params
+            .iter()
+            .map(|param| param.name.as_str())
+            .collect();
+        let mut rep = format!("(fun {} ({})", stmt.name, params.join(" "));
+        if let Some(doc) = &stmt.doc {
+            rep += &format!(" (doc {doc:?})");
+        }
+        for statement in &stmt.body {
+            rep += &format!(" {}", statement.accept(self));
+        }
+        rep += ")";
+
+        rep
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Class) -> String {
+        let mut rep = format!("(class {}", stmt.name);
+        if let Some(superclass) = &stmt.superclass {
+            rep += &format!(" < {}", superclass.name);
+        }
+        if let Some(doc) = &stmt.doc {
+            rep += &format!(" (doc {doc:?})");
+        }
+        for method in &stmt.methods {
+            rep += &format!(" {}", self.visit_function_stmt(method));
+        }
+        rep += ")";
+
+        rep
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &Return) -> String {
+        match &stmt.value {
+            Some(value) => format!("(return {})", value.accept(self)),
+            None => String::from("(return)"),
+        }
+    }
 }
 
-// Add pretty print method to parser.
+// Add pretty print method to statements.
 
-impl Parser {
+impl Stmt {
     pub fn pretty_print(&self) -> String {
         let mut printer = PrettyPrintVisitor {};
-        if let Some(expression) = self.root.as_ref() {
-            expression.accept(&mut printer)
-        } else {
-            String::from("Null Expression")
-        }
+        self.accept(&mut printer)
     }
 }
@@ -1,8 +1,13 @@
+pub mod error;
 pub mod file_utf8_reader;
-pub mod scanner;
+#[allow(clippy::module_inception)]
 pub mod parser;
+pub mod scanner;
+pub mod source_cursor;
 
+pub use crate::parser::error::*;
 pub use crate::parser::file_utf8_reader::*;
 pub use crate::parser::parser::*;
+pub use crate::parser::source_cursor::*;
 
-mod grammar;
+pub(crate) mod grammar;
@@ -2,65 +2,532 @@
 ///
 ///
 use crate::parser::grammar::*;
-use crate::parser::scanner::Token;
+use crate::parser::scanner::{Span, Token, TokenWithSpan};
+use crate::parser::{ParseError, ParseErrorKind};
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Nystrom's limit, which keeps the door open for a bytecode VM.
+const MAX_ARGUMENTS: usize = 255;
+
+// How deeply statements and expressions can nest. The resolver and
+// interpreter recurse over the tree too, so this bounds their stack use.
+const MAX_NESTING: usize = 200;
 
 // ----------------------
 // Parser implementation.
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<TokenWithSpan>,
     cursor: usize,
+    // How many nested statements and expressions we're inside.
+    nesting: usize,
+    // Doc comments aren't part of the grammar, so they're kept
+    // apart, by the index of the token that comes after them.
+    doc_comments: HashMap<usize, String>,
 
-    // Root of AST.
-    pub root: Option<Expression>,
+    // Errors found so far in this parse.
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        let mut parser = Parser { tokens, cursor: 0, root: None };
-        parser.root = Some(parser.expression());
+    pub fn new(tokens: Vec<TokenWithSpan>) -> Self {
+        let mut grammar_tokens = Vec::with_capacity(tokens.len());
+        let mut doc_comments: HashMap<usize, String> = HashMap::new();
+
+        for token in tokens {
+            match token.token {
+                // Consecutive lines make up one comment.
+                Token::DocComment(text) => {
+                    let doc = doc_comments.entry(grammar_tokens.len()).or_default();
+                    if !doc.is_empty() {
+                        doc.push('\n');
+                    }
+                    doc.push_str(&text);
+                }
+
+                _ => grammar_tokens.push(token),
+            }
+        }
+
+        Parser {
+            tokens: grammar_tokens,
+            cursor: 0,
+            nesting: 0,
+            doc_comments,
+            errors: vec![],
+        }
+    }
+
+    /// Parses the tokens into a list of statements. On failure we
+    /// synchronize and keep going, so that all errors in the
+    /// input are reported together.
+    pub fn parse_program(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = vec![];
+
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+
+                Err(error) => {
+                    // An error at an error token follows from a scan
+                    // error, which has already been reported.
+                    if *self.peek() != Token::Error {
+                        self.errors.push(error);
+                    }
+                    self.synchronize();
+                }
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // ------------
+    // Statements.
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        // Doc comments before other statements are dropped.
+        let doc = self.doc_comment();
+
+        if self.match_token(|token| matches!(token, Token::Class)) {
+            self.class_declaration(doc)
+        } else if self.match_token(|token| matches!(token, Token::Fun)) {
+            Ok(Stmt::Function(Rc::new(self.function("function", doc)?)))
+        } else if self.match_token(|token| matches!(token, Token::Var)) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        }
+    }
+
+    fn class_declaration(&mut self, doc: Option<String>) -> Result<Stmt, ParseError> {
+        let (name, name_span) = self.consume_identifier("Expected class name.")?;
+
+        let superclass = if self.match_token(|token| matches!(token, Token::Less)) {
+            let (name, span) = self.consume_identifier("Expected superclass name.")?;
+            Some(Variable {
+                name,
+                span,
+                depth: Cell::new(None),
+            })
+        } else {
+            None
+        };
+
+        self.consume(
+            |token| matches!(token, Token::LeftBrace),
+            "Expected '{' before class body.",
+        )?;
+        let mut methods = vec![];
+        while !self.check(|token| matches!(token, Token::RightBrace)) && !self.is_at_end() {
+            let doc = self.doc_comment();
+            methods.push(Rc::new(self.function("method", doc)?));
+        }
+        self.consume(
+            |token| matches!(token, Token::RightBrace),
+            "Expected '}' after class body.",
+        )?;
+
+        Ok(Stmt::Class(Class {
+            doc,
+            name,
+            name_span,
+            superclass,
+            methods,
+        }))
+    }
 
-        parser
+    // The kind is used in error messages.
+    fn function(&mut self, kind: &str, doc: Option<String>) -> Result<Function, ParseError> {
+        let (name, name_span) = self.consume_identifier(&format!("Expected {kind} name."))?;
+        self.consume(
+            |token| matches!(token, Token::LeftParen),
+            &format!("Expected '(' after {kind} name."),
+        )?;
+
+        let mut params = vec![];
+        if !self.check(|token| matches!(token, Token::RightParen)) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.errors.push(self.error(
+                        ParseErrorKind::TooManyArguments,
+                        &format!("Can't have more than {MAX_ARGUMENTS} parameters."),
+                    ));
+                }
+
+                let (name, span) = self.consume_identifier("Expected parameter name.")?;
+                params.push(Parameter { name, span });
+
+                if !self.match_token(|token| matches!(token, Token::Comma)) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            |token| matches!(token, Token::RightParen),
+            "Expected ')' after parameters.",
+        )?;
+
+        self.consume(
+            |token| matches!(token, Token::LeftBrace),
+            &format!("Expected '{{' before {kind} body."),
+        )?;
+        let body = self.block()?;
+
+        Ok(Function {
+            doc,
+            name,
+            name_span,
+            params,
+            body,
+        })
     }
 
-    fn expression(&mut self) -> Expression {
-        self.equality()
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let var = self.var_clause()?;
+        self.end_statement();
+
+        Ok(var)
     }
 
-    fn equality(&mut self) -> Expression {
-        let pred = |token: &Token| match token {
-            Token::EqualEqual | Token::BangEqual => true,
-            _ => false,
+    // A var declaration without its optional ';', which is
+    // required when it's the initializer of a for loop.
+    fn var_clause(&mut self) -> Result<Stmt, ParseError> {
+        let (name, name_span) = self.consume_identifier("Expected variable name.")?;
+
+        let initializer = if self.match_token(|token| matches!(token, Token::Equal)) {
+            Some(self.expression()?)
+        } else {
+            None
         };
 
-        let mut expr = self.comparison();
+        Ok(Stmt::Var(Var {
+            name,
+            name_span,
+            initializer,
+        }))
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        match self.peek() {
+            Token::For => {
+                self.cursor += 1;
+                self.for_statement()
+            }
+            Token::If => {
+                self.cursor += 1;
+                self.if_statement()
+            }
+            Token::Print => {
+                self.cursor += 1;
+                let expression = self.expression()?;
+                self.end_statement();
+
+                Ok(Stmt::Print(expression))
+            }
+            Token::Return => {
+                self.cursor += 1;
+                self.return_statement()
+            }
+            Token::While => {
+                self.cursor += 1;
+                self.while_statement()
+            }
+            Token::LeftBrace => {
+                self.cursor += 1;
+                Ok(Stmt::Block(self.block()?))
+            }
+
+            _ => {
+                let expression = self.expression()?;
+                self.end_statement();
+
+                Ok(Stmt::Expression(expression))
+            }
+        }
+    }
+
+    // We desugar for loops into while loops, as in ch. 9.
+    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(
+            |token| matches!(token, Token::LeftParen),
+            "Expected '(' after 'for'.",
+        )?;
+
+        let initializer = if self.match_token(|token| matches!(token, Token::Semicolon)) {
+            None
+        } else {
+            let initializer = if self.match_token(|token| matches!(token, Token::Var)) {
+                self.var_clause()?
+            } else {
+                Stmt::Expression(self.expression()?)
+            };
+            self.consume(
+                |token| matches!(token, Token::Semicolon),
+                "Expected ';' after loop initializer.",
+            )?;
+
+            Some(initializer)
+        };
+
+        let condition = if self.check(|token| matches!(token, Token::Semicolon)) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        let semicolon_span = self.peek_span();
+        self.consume(
+            |token| matches!(token, Token::Semicolon),
+            "Expected ';' after loop condition.",
+        )?;
+
+        let increment = if self.check(|token| matches!(token, Token::RightParen)) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            |token| matches!(token, Token::RightParen),
+            "Expected ')' after for clauses.",
+        )?;
+
+        let mut body = self.nested(Self::statement)?;
+
+        if let Some(increment) = increment {
+            body = Stmt::Block(vec![body, Stmt::Expression(increment)]);
+        }
+
+        // A missing condition loops forever.
+        let condition = condition.unwrap_or(Expression::Literal(Literal::True, semicolon_span));
+        body = Stmt::While(While {
+            condition,
+            body: Box::new(body),
+        });
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let condition = self.parenthesized_condition("if")?;
+
+        let then_branch = Box::new(self.nested(Self::statement)?);
+        // An else binds to the nearest if.
+        let else_branch = if self.match_token(|token| matches!(token, Token::Else)) {
+            Some(Box::new(self.nested(Self::statement)?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(If {
+            condition,
+            then_branch,
+            else_branch,
+        }))
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword_span = self.previous_span();
+
+        // Without a required ';', the value is absent
+        // when the statement can't continue.
+        let has_value = !self.is_at_end()
+            && !self.check(|token| matches!(token, Token::Semicolon | Token::RightBrace));
+        let value = if has_value {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.end_statement();
+
+        Ok(Stmt::Return(Return {
+            keyword_span,
+            value,
+        }))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let condition = self.parenthesized_condition("while")?;
+        let body = Box::new(self.nested(Self::statement)?);
+
+        Ok(Stmt::While(While { condition, body }))
+    }
+
+    fn parenthesized_condition(&mut self, keyword: &str) -> Result<Expression, ParseError> {
+        self.consume(
+            |token| matches!(token, Token::LeftParen),
+            &format!("Expected '(' after '{keyword}'."),
+        )?;
+        let condition = self.expression()?;
+        self.consume(
+            |token| matches!(token, Token::RightParen),
+            &format!("Expected ')' after {keyword} condition."),
+        )?;
+
+        Ok(condition)
+    }
+
+    // We've already consumed the '{'.
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = vec![];
+
+        while !self.check(|token| matches!(token, Token::RightBrace)) && !self.is_at_end() {
+            statements.push(self.nested(Self::declaration)?);
+        }
+        self.consume(
+            |token| matches!(token, Token::RightBrace),
+            "Expected '}' after block.",
+        )?;
+
+        Ok(statements)
+    }
+
+    /// Statements may optionally end with a ';'.
+    fn end_statement(&mut self) {
+        self.match_token(|token| matches!(token, Token::Semicolon));
+    }
+
+    // -------------
+    // Expressions.
+
+    fn expression(&mut self) -> Result<Expression, ParseError> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expression, ParseError> {
+        let expr = self.or()?;
+
+        if self.match_token(|token| matches!(token, Token::Equal)) {
+            // Assignment is right-associative.
+            let value = self.nested(Self::assignment)?;
+
+            // We only know the left side is an assignment
+            // target once we reach the '='.
+            match expr {
+                Expression::Variable(Variable { name, span, .. }) => {
+                    return Ok(Expression::Assign(Assign {
+                        name,
+                        name_span: span,
+                        value: Box::new(value),
+                        depth: Cell::new(None),
+                    }));
+                }
+
+                // A property access becomes a property assignment.
+                Expression::Get(Get {
+                    object,
+                    name,
+                    name_span,
+                }) => {
+                    return Ok(Expression::Set(Set {
+                        object,
+                        name,
+                        name_span,
+                        value: Box::new(value),
+                    }));
+                }
+
+                _ => {}
+            }
+
+            // The parser isn't confused here, so we
+            // record the error without synchronizing.
+            self.errors.push(ParseError::new(
+                ParseErrorKind::InvalidAssignmentTarget,
+                expr.span(),
+                "Invalid assignment target.",
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expression, ParseError> {
+        let nesting = self.nesting;
+        let mut expr = self.and()?;
+
+        while self.match_token(|token| matches!(token, Token::Or)) {
+            self.link()?;
+            let right = self.and()?;
+            expr = Expression::Logical(Logical {
+                left: Box::new(expr),
+                operator: LogicalOp::Or,
+                right: Box::new(right),
+            });
+        }
+
+        self.nesting = nesting;
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expression, ParseError> {
+        let nesting = self.nesting;
+        let mut expr = self.equality()?;
+
+        while self.match_token(|token| matches!(token, Token::And)) {
+            self.link()?;
+            let right = self.equality()?;
+            expr = Expression::Logical(Logical {
+                left: Box::new(expr),
+                operator: LogicalOp::And,
+                right: Box::new(right),
+            });
+        }
+
+        self.nesting = nesting;
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expression, ParseError> {
+        let pred = |token: &Token| matches!(token, Token::EqualEqual | Token::BangEqual);
+
+        let nesting = self.nesting;
+        let mut expr = self.comparison()?;
 
         while self.match_token(pred) {
+            self.link()?;
             let operator = match self.previous() {
                 Token::EqualEqual => BinaryOp::EqualEqual,
                 Token::BangEqual => BinaryOp::BangEqual,
                 _ => unreachable!(),
             };
-            let right = self.comparison();
+            let operator_span = self.previous_span();
+            let right = self.comparison()?;
             expr = Expression::Binary(Binary {
                 left: Box::new(expr),
                 operator,
+                operator_span,
                 right: Box::new(right),
             });
         }
 
-        expr
+        self.nesting = nesting;
+        Ok(expr)
     }
 
-    fn comparison(&mut self) -> Expression {
-        let pred = |token: &Token| match token {
-            Token::Greater | Token::GreaterEqual | Token::Less | Token::LessEqual => true,
-            _ => false,
+    fn comparison(&mut self) -> Result<Expression, ParseError> {
+        let pred = |token: &Token| {
+            matches!(
+                token,
+                Token::Greater | Token::GreaterEqual | Token::Less | Token::LessEqual
+            )
         };
 
-        let mut expr = self.term();
+        let nesting = self.nesting;
+        let mut expr = self.term()?;
 
         while self.match_token(pred) {
+            self.link()?;
             let operator = match self.previous() {
                 Token::Greater => BinaryOp::Greater,
                 Token::GreaterEqual => BinaryOp::GreaterEqual,
@@ -68,72 +535,77 @@ impl Parser {
                 Token::LessEqual => BinaryOp::LessEqual,
                 _ => unreachable!(),
             };
-            let right = self.term();
+            let operator_span = self.previous_span();
+            let right = self.term()?;
             expr = Expression::Binary(Binary {
                 left: Box::new(expr),
                 operator,
+                operator_span,
                 right: Box::new(right),
             })
         }
 
-        expr
+        self.nesting = nesting;
+        Ok(expr)
     }
 
-    fn term(&mut self) -> Expression {
-        let pred = |token: &Token| match token {
-            Token::Plus | Token::Minus => true,
-            _ => false,
-        };
+    fn term(&mut self) -> Result<Expression, ParseError> {
+        let pred = |token: &Token| matches!(token, Token::Plus | Token::Minus);
 
-        let mut expr = self.factor();
+        let nesting = self.nesting;
+        let mut expr = self.factor()?;
 
         while self.match_token(pred) {
+            self.link()?;
             let operator = match self.previous() {
                 Token::Plus => BinaryOp::Plus,
                 Token::Minus => BinaryOp::Minus,
                 _ => unreachable!(),
             };
-            let right = self.factor();
+            let operator_span = self.previous_span();
+            let right = self.factor()?;
             expr = Expression::Binary(Binary {
                 left: Box::new(expr),
                 operator,
+                operator_span,
                 right: Box::new(right),
             })
         }
 
-        expr
+        self.nesting = nesting;
+        Ok(expr)
     }
 
-    fn factor(&mut self) -> Expression {
-        let pred = |token: &Token| match token {
-            Token::Slash | Token::Star => true,
-            _ => false,
-        };
+    fn factor(&mut self) -> Result<Expression, ParseError> {
+        let pred = |token: &Token| matches!(token, Token::Slash | Token::Star | Token::Percent);
 
-        let mut expr = self.unary();
+        let nesting = self.nesting;
+        let mut expr = self.unary()?;
 
         while self.match_token(pred) {
+            self.link()?;
             let operator = match self.previous() {
                 Token::Slash => BinaryOp::Slash,
                 Token::Star => BinaryOp::Star,
+                Token::Percent => BinaryOp::Percent,
                 _ => unreachable!(),
             };
-            let right = self.unary();
+            let operator_span = self.previous_span();
+            let right = self.unary()?;
             expr = Expression::Binary(Binary {
                 left: Box::new(expr),
                 operator,
+                operator_span,
                 right: Box::new(right),
             })
         }
 
-        expr
+        self.nesting = nesting;
+        Ok(expr)
     }
 
-    fn unary(&mut self) -> Expression {
-        let pred = |token: &Token| match token {
-            Token::Bang | Token::Minus => true,
-            _ => false,
-        };
+    fn unary(&mut self) -> Result<Expression, ParseError> {
+        let pred = |token: &Token| matches!(token, Token::Bang | Token::Minus);
 
         if self.match_token(pred) {
             let operator = match self.previous() {
@@ -141,46 +613,308 @@ impl Parser {
                 Token::Minus => UnaryOp::Minus,
                 _ => unreachable!(),
             };
-            let right = self.unary();
-            return Expression::Unary(Unary {
+            let operator_span = self.previous_span();
+            let right = self.nested(Self::unary)?;
+            return Ok(Expression::Unary(Unary {
                 operator,
+                operator_span,
                 expr: Box::new(right),
-            });
+            }));
         }
 
-        self.primary()
+        self.call()
     }
 
-    fn primary(&mut self) -> Expression {
-        if self.cursor >= self.tokens.len() {
-            panic!("Out of tokens while parsing expression.");
+    fn call(&mut self) -> Result<Expression, ParseError> {
+        let nesting = self.nesting;
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_token(|token| matches!(token, Token::LeftParen)) {
+                self.link()?;
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(|token| matches!(token, Token::Period)) {
+                self.link()?;
+                let (name, name_span) =
+                    self.consume_identifier("Expected property name after '.'.")?;
+                expr = Expression::Get(Get {
+                    object: Box::new(expr),
+                    name,
+                    name_span,
+                });
+            } else {
+                break;
+            }
         }
 
-        // Consume current token.
-        let token = &self.tokens[self.cursor];
-        self.cursor += 1;
+        self.nesting = nesting;
+        Ok(expr)
+    }
+
+    // We've already consumed the '('.
+    fn finish_call(&mut self, callee: Expression) -> Result<Expression, ParseError> {
+        let mut arguments = vec![];
+
+        if !self.check(|token| matches!(token, Token::RightParen)) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.errors.push(self.error(
+                        ParseErrorKind::TooManyArguments,
+                        &format!("Can't have more than {MAX_ARGUMENTS} arguments."),
+                    ));
+                }
+                arguments.push(self.expression()?);
+
+                if !self.match_token(|token| matches!(token, Token::Comma)) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            |token| matches!(token, Token::RightParen),
+            "Expected ')' after arguments.",
+        )?;
+
+        Ok(Expression::Call(Call {
+            callee: Box::new(callee),
+            paren_span: self.previous_span(),
+            arguments,
+        }))
+    }
+
+    fn primary(&mut self) -> Result<Expression, ParseError> {
+        let span = self.peek_span();
+
+        let expr = match self.peek() {
+            Token::False => Expression::Literal(Literal::False, span),
+            Token::True => Expression::Literal(Literal::True, span),
+            Token::Nil => Expression::Literal(Literal::Nil, span),
+
+            Token::Number(number) => Expression::Literal(Literal::Number(number.clone()), span),
+            Token::String(val) => Expression::Literal(Literal::String(val.to_owned()), span),
+
+            Token::Identifier(name) => Expression::Variable(Variable {
+                name: name.to_owned(),
+                span,
+                depth: Cell::new(None),
+            }),
+
+            Token::InterpolationStart(text) => {
+                let text = text.to_owned();
+                self.cursor += 1;
+                return self.interpolation(text, span);
+            }
 
-        match token {
-            Token::False => Expression::Literal(Literal::False),
-            Token::True => Expression::Literal(Literal::True),
-            Token::Nil => Expression::Literal(Literal::Nil),
+            Token::This => Expression::This(This {
+                span,
+                depth: Cell::new(None),
+            }),
 
-            Token::Number(val) => Expression::Literal(Literal::Number(val.to_owned())),
-            Token::String(val) => Expression::Literal(Literal::String(val.to_owned())),
+            Token::Super => {
+                self.cursor += 1;
+                self.consume(
+                    |token| matches!(token, Token::Period),
+                    "Expected '.' after 'super'.",
+                )?;
+                let (method, method_span) =
+                    self.consume_identifier("Expected superclass method name.")?;
+
+                return Ok(Expression::Super(Super {
+                    keyword_span: span,
+                    method,
+                    method_span,
+                    depth: Cell::new(None),
+                }));
+            }
 
             Token::LeftParen => {
-                let expr = self.expression();
+                self.cursor += 1;
+                let expr = self.expression()?;
 
                 // Next token should be right paren; consume it.
-                if self.tokens[self.cursor] != Token::RightParen {
-                    panic!("Expected ')'.");
+                self.consume(
+                    |token| matches!(token, Token::RightParen),
+                    "Expected ')' after expression.",
+                )?;
+
+                return Ok(Expression::Grouping(
+                    Box::new(expr),
+                    span.to(self.previous_span()),
+                ));
+            }
+
+            // Leave the token for synchronize to skip.
+            _ => return Err(self.error(ParseErrorKind::ExpectedExpression, "Expected expression.")),
+        };
+
+        // Consume the literal, identifier or this token.
+        self.cursor += 1;
+
+        Ok(expr)
+    }
+
+    // We've consumed the start of an interpolated string, holding
+    // the text up to its first expression, and its span.
+    fn interpolation(&mut self, text: String, span: Span) -> Result<Expression, ParseError> {
+        let mut parts = vec![];
+        let mut text = text;
+
+        loop {
+            if !text.is_empty() {
+                parts.push(InterpolationPart::Text(text));
+            }
+            parts.push(InterpolationPart::Expression(self.expression()?));
+
+            let is_end = matches!(self.peek(), Token::InterpolationEnd(_));
+            text = match self.peek() {
+                Token::InterpolationMiddle(text) | Token::InterpolationEnd(text) => text.to_owned(),
+
+                _ => {
+                    return Err(self.error(
+                        ParseErrorKind::ExpectedToken,
+                        "Expected '}' after interpolated expression.",
+                    ))
                 }
-                self.cursor += 1;
+            };
+            self.cursor += 1;
+
+            if is_end {
+                break;
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(InterpolationPart::Text(text));
+        }
+
+        Ok(Expression::Interpolation(Interpolation {
+            parts,
+            span: span.to(self.previous_span()),
+        }))
+    }
+
+    // ----------------
+    // Error recovery.
+
+    /// Discards tokens until we're probably at the start of the
+    /// next statement, as in Nystrom's panic mode recovery.
+    fn synchronize(&mut self) {
+        if !self.is_at_end() {
+            self.cursor += 1;
+        }
 
-                Expression::Grouping(Box::new(expr))
+        while !self.is_at_end() {
+            if *self.previous() == Token::Semicolon {
+                return;
             }
 
-            _ => unreachable!(),
+            match self.peek() {
+                Token::Class
+                | Token::Fun
+                | Token::Var
+                | Token::For
+                | Token::If
+                | Token::While
+                | Token::Print
+                | Token::Return => return,
+
+                _ => self.cursor += 1,
+            }
+        }
+    }
+
+    /// Parses something nested inside what we're parsing. All recursion
+    /// in the grammar goes through here, and chains like `a + b + c`,
+    /// which build trees as deep as they're long without recursing,
+    /// count each link with `link`. So source nested too deeply for the
+    /// resolver and interpreter to walk is an error, rather than
+    /// overflowing the stack.
+    fn nested<T, F>(&mut self, parse: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ParseError>,
+    {
+        let nesting = self.nesting;
+        self.link()?;
+        let result = parse(self);
+        // This also drops the links of a chain that ended in an error.
+        self.nesting = nesting;
+
+        result
+    }
+
+    // Goes a level deeper, for another link of a chain. The chain
+    // restores the nesting when it ends.
+    fn link(&mut self) -> Result<(), ParseError> {
+        if self.nesting >= MAX_NESTING {
+            return Err(self.error(
+                ParseErrorKind::TooDeeplyNested,
+                &format!("Can't nest more than {MAX_NESTING} levels deep."),
+            ));
+        }
+        self.nesting += 1;
+
+        Ok(())
+    }
+
+    /// Error at the current token.
+    fn error(&self, kind: ParseErrorKind, message: &str) -> ParseError {
+        ParseError::new(kind, self.peek_span(), message)
+    }
+
+    // ----------------
+    // Token helpers.
+
+    fn consume<F>(&mut self, pred: F, message: &str) -> Result<(), ParseError>
+    where
+        F: Fn(&Token) -> bool,
+    {
+        if self.match_token(pred) {
+            Ok(())
+        } else {
+            Err(self.error(ParseErrorKind::ExpectedToken, message))
+        }
+    }
+
+    fn consume_identifier(&mut self, message: &str) -> Result<(String, Span), ParseError> {
+        match self.peek().clone() {
+            Token::Identifier(name) => {
+                self.cursor += 1;
+                Ok((name, self.previous_span()))
+            }
+
+            _ => Err(self.error(ParseErrorKind::ExpectedToken, message)),
+        }
+    }
+
+    /// The doc comment just before the current token, if any.
+    fn doc_comment(&mut self) -> Option<String> {
+        self.doc_comments.remove(&self.cursor)
+    }
+
+    /// Whether the current token matches, without consuming it.
+    fn check<F>(&self, pred: F) -> bool
+    where
+        F: Fn(&Token) -> bool,
+    {
+        pred(self.peek())
+    }
+
+    fn is_at_end(&self) -> bool {
+        *self.peek() == Token::EOF
+    }
+
+    /// The current token, or EOF if we've run out.
+    fn peek(&self) -> &Token {
+        match self.tokens.get(self.cursor) {
+            Some(token) => &token.token,
+            None => &Token::EOF,
+        }
+    }
+
+    fn peek_span(&self) -> Span {
+        match self.tokens.get(self.cursor).or(self.tokens.last()) {
+            Some(token) => token.span,
+            None => Span::default(),
         }
     }
 
@@ -189,17 +923,25 @@ impl Parser {
             panic!("Previous called at index 0.");
         }
 
-        &self.tokens[self.cursor - 1]
+        &self.tokens[self.cursor - 1].token
+    }
+
+    fn previous_span(&self) -> Span {
+        if self.cursor < 1 {
+            panic!("Previous called at index 0.");
+        }
+
+        self.tokens[self.cursor - 1].span
     }
 
     fn match_token<F>(&mut self, pred: F) -> bool
     where
         F: Fn(&Token) -> bool,
     {
-        if self.cursor == self.tokens.len() {
+        if self.cursor >= self.tokens.len() {
             return false;
         }
-        if pred(&self.tokens[self.cursor]) {
+        if pred(&self.tokens[self.cursor].token) {
             self.cursor += 1;
 
             true
@@ -208,3 +950,110 @@ impl Parser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::interpreter::STACK_SIZE;
+    use crate::parser::scanner::Scanner;
+
+    use std::thread;
+
+    // Parses on a thread with the stack the interpreter runs with, since
+    // the deepest nesting allowed takes more than a test thread has.
+    fn parse(source: &str) -> Result<(), Vec<ParseError>> {
+        let source = source.to_owned();
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let mut scanner = Scanner::from_str(&source);
+                assert!(scanner.scan_tokens().is_empty());
+                Parser::new(scanner.tokens).parse_program().map(|_| ())
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    fn nesting_errors(source: &str) -> usize {
+        match parse(source) {
+            Ok(()) => 0,
+            Err(errors) => errors
+                .iter()
+                .filter(|error| error.kind == ParseErrorKind::TooDeeplyNested)
+                .count(),
+        }
+    }
+
+    #[test]
+    fn limits_nested_parentheses() {
+        let source = format!("print {}1{};", "(".repeat(5000), ")".repeat(5000));
+        assert_eq!(nesting_errors(&source), 1);
+    }
+
+    #[test]
+    fn limits_long_operator_chains() {
+        // Each link makes the tree a level deeper, though
+        // parsing the chain doesn't recurse.
+        for operator in ["+", "-", "*", "/", "%", "<", "==", "and", "or"] {
+            let source = format!("print 1{};", format!(" {operator} 1").repeat(30_000));
+            assert_eq!(nesting_errors(&source), 1, "operator {operator}");
+        }
+    }
+
+    #[test]
+    fn limits_long_call_chains() {
+        assert_eq!(nesting_errors(&format!("f{};", "()".repeat(30_000))), 1);
+        assert_eq!(nesting_errors(&format!("a{};", ".b".repeat(30_000))), 1);
+    }
+
+    #[test]
+    fn counts_each_chain_separately() {
+        // 150 products of two factors, each a short chain of its own.
+        let source = format!("print 1{};", " + 2 * 3".repeat(150));
+        assert!(parse(&source).is_ok());
+
+        let statements = "print 1 + 1;\n".repeat(MAX_NESTING * 2);
+        assert!(parse(&statements).is_ok());
+    }
+
+    fn statements(source: &str) -> Vec<Stmt> {
+        let mut scanner = Scanner::from_str(source);
+        assert!(scanner.scan_tokens().is_empty());
+        Parser::new(scanner.tokens).parse_program().unwrap()
+    }
+
+    #[test]
+    fn attaches_doc_comments_to_functions() {
+        let statements = statements("/// Adds one.\n/// Or more.\nfun f(x) { return x + 1; }");
+
+        let [Stmt::Function(function)] = &statements[..] else {
+            panic!("Expected one function.");
+        };
+        assert_eq!(function.doc.as_deref(), Some("Adds one.\nOr more."));
+    }
+
+    #[test]
+    fn attaches_doc_comments_to_methods() {
+        let statements =
+            statements("/// A greeter.\nclass A {\n  /// Says hi.\n  hi() {}\n  bye() {}\n}");
+
+        let [Stmt::Class(class)] = &statements[..] else {
+            panic!("Expected one class.");
+        };
+        assert_eq!(class.doc.as_deref(), Some("A greeter."));
+        assert_eq!(class.methods[0].doc.as_deref(), Some("Says hi."));
+        assert_eq!(class.methods[1].doc, None);
+    }
+
+    #[test]
+    fn drops_doc_comments_before_other_statements() {
+        let statements = statements("/// Not a declaration.\nprint 1;\nfun f() {}");
+
+        let [Stmt::Print(_), Stmt::Function(function)] = &statements[..] else {
+            panic!("Expected a print and a function.");
+        };
+        assert_eq!(function.doc, None);
+    }
+}
@@ -4,10 +4,15 @@
 ///
 /// Created by sean on 12/22/2024.
 ///
-use crate::parser::FileUtf8Reader;
+use crate::number::{BigInt, Decimal};
+use crate::parser::{
+    escape_bytes, DecodeError, DecodeErrorKind, FileUtf8Reader, Position, ScanError, ScanErrorKind,
+    SourceCursor,
+};
 
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::Read;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -22,6 +27,7 @@ pub enum Token {
     Plus,
     Semicolon,
     Star,
+    Percent,
     // Operators, potentially two-token.
     Bang,
     BangEqual,
@@ -35,8 +41,14 @@ pub enum Token {
     Slash,
     // String containing text value.
     String(String),
-    // Number containing text of literal.
-    Number(String),
+    // Pieces of an interpolated string, like "a ${b} c ${d} e", holding
+    // the text before the first "${", between a "}" and the next "${",
+    // and after the last "}". Tokens for each expression come between.
+    InterpolationStart(String),
+    InterpolationMiddle(String),
+    InterpolationEnd(String),
+    // Number literal, already parsed.
+    Number(NumberLiteral),
     // Keywords.
     And,
     Class,
@@ -56,10 +68,69 @@ pub enum Token {
     While,
     // User-defined identifier.
     Identifier(String),
+    // Text of a `///` comment, after the slashes and a space.
+    DocComment(String),
+    // Stands in for malformed source the scanner has already
+    // reported, so the parser doesn't report it again.
+    Error,
     // Special token to aid parser.
+    #[allow(clippy::upper_case_acronyms)]
     EOF,
 }
 
+/// Value of a number literal. Literals without a fraction or
+/// exponent are integers, which are big ints if they don't fit
+/// in an i64. Literals with a fraction, like `0.1`, or a `d`
+/// suffix are exact decimals, and those with an exponent are
+/// floats.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberLiteral {
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    Decimal(Decimal),
+}
+
+/// Source range covered by a token or AST node. Line and column
+/// (both 1-based) are where it starts, and the byte offsets give
+/// the half-open range `start_byte..end_byte` in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    // The same column, counted in UTF-16 code units.
+    pub utf16_column: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+impl Span {
+    /// Empty span at a position.
+    pub fn at(position: Position) -> Span {
+        Span {
+            line: position.line,
+            column: position.column,
+            utf16_column: position.utf16_column,
+            start_byte: position.byte_offset,
+            end_byte: position.byte_offset,
+        }
+    }
+
+    /// Span from the start of this one to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end_byte: other.end_byte,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenWithSpan {
+    pub token: Token,
+    pub span: Span,
+}
+
 fn get_keywords_map() -> HashMap<String, Token> {
     let mut keywords: HashMap<String, Token> = HashMap::new();
 
@@ -83,135 +154,226 @@ fn get_keywords_map() -> HashMap<String, Token> {
     keywords
 }
 
+// Where the scanner gets its chars from.
+enum CharSource {
+    Reader(FileUtf8Reader<Box<dyn Read>>),
+    // Source that's already in memory is
+    // already valid utf-8, so needs no decoding.
+    Str { source: String, position: usize },
+}
+
+impl Iterator for CharSource {
+    type Item = Result<char, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            CharSource::Reader(reader) => reader.next(),
+
+            CharSource::Str { source, position } => {
+                let c = source[*position..].chars().next()?;
+                *position += c.len_utf8();
+                Some(Ok(c))
+            }
+        }
+    }
+}
+
+// How a piece of a string literal ended.
+enum StringEnd {
+    Quote,
+    // At the "${" starting an interpolated expression.
+    Interpolation,
+    Unterminated,
+}
+
+// An interpolated expression we're in the middle of scanning.
+struct OpenInterpolation {
+    // Braces opened in the expression and not yet closed,
+    // so we can tell which '}' ends the expression.
+    brace_depth: usize,
+    // Span of the string's opening quote.
+    string_start: Span,
+}
+
 pub struct Scanner {
-    pub tokens: Vec<Token>,
+    pub tokens: Vec<TokenWithSpan>,
+    pub errors: Vec<ScanError>,
 
     keywords_map: HashMap<String, Token>,
-    reader: FileUtf8Reader,
+    reader: SourceCursor<CharSource>,
 
     current_char: Option<char>,
     next_char: Option<char>,
     third_char: Option<char>,
 
-    current_line: usize,
+    // Positions of the three chars above in the source. Once
+    // we're past the end, these are the end of the source.
+    current_position: Position,
+    next_position: Position,
+    third_position: Position,
+    // Span of the token being scanned; its end is filled in by `add_token`.
+    token_start: Span,
+    // Innermost last, since strings can nest in interpolations.
+    open_interpolations: Vec<OpenInterpolation>,
 }
 
 impl Scanner {
     pub fn new(file: File) -> Scanner {
-        let reader = FileUtf8Reader::new(file);
+        Self::from_reader(file)
+    }
+
+    /// Scanner over any source of utf-8 bytes.
+    pub fn from_reader(reader: impl Read + 'static) -> Scanner {
+        let reader = FileUtf8Reader::from_reader(Box::new(reader) as Box<dyn Read>);
+        Self::with_source(CharSource::Reader(reader))
+    }
+
+    /// Scanner over source text that's already in memory.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(source: &str) -> Scanner {
+        Self::with_source(CharSource::Str {
+            source: source.to_owned(),
+            position: 0,
+        })
+    }
+
+    fn with_source(reader: CharSource) -> Scanner {
         let mut scanner = Scanner {
             tokens: vec![],
+            errors: vec![],
             keywords_map: get_keywords_map(),
-            reader,
+            reader: SourceCursor::new(reader),
             current_char: None,
             next_char: None,
             third_char: None,
-            current_line: 1,
+            current_position: Position::default(),
+            next_position: Position::default(),
+            third_position: Position::default(),
+            token_start: Span::default(),
+            open_interpolations: vec![],
         };
 
-        scanner.current_char = scanner.reader.next();
-        scanner.next_char = scanner.reader.next();
-        scanner.third_char = scanner.reader.next();
+        // Fill all three chars of lookahead.
+        for _ in 0..3 {
+            scanner.advance();
+        }
 
         scanner
     }
 
-    pub fn scan_tokens(&mut self) {
+    /// Scans the whole source. Lexical errors don't stop the scan;
+    /// they are collected and returned, and are empty on success.
+    pub fn scan_tokens(&mut self) -> &[ScanError] {
         while !self.is_at_end() {
             self.scan_token()
         }
 
+        self.token_start = self.current_span();
+
+        // Strings whose interpolated expressions never ended.
+        while let Some(open) = self.open_interpolations.pop() {
+            self.add_error_from(
+                open.string_start,
+                ScanErrorKind::UnterminatedString,
+                String::from("\""),
+            );
+            self.add_token(Token::Error);
+        }
+
         self.add_token(Token::EOF);
+
+        &self.errors
     }
 
     fn scan_token(&mut self) {
+        self.token_start = self.current_span();
+
         match self.current_char {
             None => panic!("Should not attempt token scan with no remaining characters."),
 
             Some(c) => match c {
                 '(' => self.add_token(Token::LeftParen),
                 ')' => self.add_token(Token::RightParen),
-                '{' => self.add_token(Token::LeftBrace),
-                '}' => self.add_token(Token::RightBrace),
+                '{' => {
+                    if let Some(open) = self.open_interpolations.last_mut() {
+                        open.brace_depth += 1;
+                    }
+                    self.add_token(Token::LeftBrace);
+                }
+                '}' => match self.open_interpolations.last().map(|open| open.brace_depth) {
+                    // This ends an interpolated expression,
+                    // so carry on with the rest of its string.
+                    Some(0) => {
+                        let open = self.open_interpolations.pop().unwrap();
+                        self.scan_string(Some(open.string_start));
+                    }
+                    Some(_) => {
+                        self.open_interpolations.last_mut().unwrap().brace_depth -= 1;
+                        self.add_token(Token::RightBrace);
+                    }
+
+                    None => self.add_token(Token::RightBrace),
+                },
                 ',' => self.add_token(Token::Comma),
                 '.' => self.add_token(Token::Period),
                 '-' => self.add_token(Token::Minus),
                 '+' => self.add_token(Token::Plus),
                 ';' => self.add_token(Token::Semicolon),
                 '*' => self.add_token(Token::Star),
+                '%' => self.add_token(Token::Percent),
 
                 // Operators requiring one lookahead.
                 '!' => {
                     if self.next_char == Some('=') {
-                        self.add_token(Token::BangEqual);
                         self.advance();
+                        self.add_token(Token::BangEqual);
                     } else {
                         self.add_token(Token::Bang);
                     }
                 }
                 '=' => {
                     if self.next_char == Some('=') {
-                        self.add_token(Token::EqualEqual);
                         self.advance();
+                        self.add_token(Token::EqualEqual);
                     } else {
                         self.add_token(Token::Equal);
                     }
                 }
                 '<' => {
                     if self.next_char == Some('=') {
-                        self.add_token(Token::LessEqual);
                         self.advance();
+                        self.add_token(Token::LessEqual);
                     } else {
                         self.add_token(Token::Less);
                     }
                 }
                 '>' => {
                     if self.next_char == Some('=') {
-                        self.add_token(Token::GreaterEqual);
                         self.advance();
+                        self.add_token(Token::GreaterEqual);
                     } else {
                         self.add_token(Token::Greater);
                     }
                 }
 
                 // Either start of comment or division operator.
-                '/' => {
-                    if self.next_char == Some('/') {
-                        self.consume_rest_of_line();
-                        self.current_line += 1;
-                    } else {
-                        self.add_token(Token::Slash);
-                    }
-                }
+                '/' => match self.next_char {
+                    Some('/') if self.third_char == Some('/') => self.scan_doc_comment(),
+                    Some('/') => self.consume_rest_of_line(),
+                    Some('*') => self.skip_block_comment(),
+
+                    _ => self.add_token(Token::Slash),
+                },
 
                 // String literal.
-                '"' => {
-                    let starting_line_number = self.current_line;
-                    if let Some(text) = self.get_string_literal() {
-                        self.add_token(Token::String(text));
-                    } else {
-                        // TODO: Properly handle unterminated string literal.
-                        println!(
-                            "Encountered unterminated string literal starting on line {}.",
-                            starting_line_number
-                        );
-                    }
-                }
+                '"' => self.scan_string(None),
 
                 // Numeric literal
-                c if c.is_digit(10) => {
-                    match self.get_numeric_literal() {
-                        Some(string) => self.add_token(Token::Number(string)),
-
-                        None => {
-                            // TODO: Properly handle invalid numeric literal.
-                            println!(
-                                "Encountered invalid numeric literal on line {}.",
-                                self.current_line
-                            );
-                        }
-                    }
-                }
+                c if c.is_ascii_digit() => match self.get_numeric_literal() {
+                    Ok(number) => self.add_token(Token::Number(number)),
+
+                    Err((kind, text)) => self.add_error(kind, text),
+                },
 
                 // Either a user-defined identifier or a reserved word.
                 c if Self::is_identifier_char(&c) => {
@@ -227,14 +389,10 @@ impl Scanner {
                 // We just ignore whitespace.
                 ' ' | '\t' | '\r' => {}
 
-                // Later we may want to track line numbers.
-                '\n' => {
-                    self.current_line += 1;
-                }
+                // Whitespace too, since the reader tracks lines for us.
+                '\n' => {}
 
-                _ => {
-                    // TODO: Handle unrecognized tokens.
-                }
+                c => self.add_error(ScanErrorKind::UnexpectedCharacter, c.to_string()),
             },
         }
 
@@ -244,22 +402,84 @@ impl Scanner {
 
     /// This fn exists to avoid duplicating the push call for each token,
     /// And it will be useful if we start recording additional information.
+    ///
+    /// NOTE: This must be called while on the last char of the token,
+    /// since that is where the token's span ends.
     fn add_token(&mut self, token: Token) {
-        self.tokens.push(token);
+        let end_byte =
+            self.current_position.byte_offset + self.current_char.map_or(0, char::len_utf8);
+        let span = Span {
+            end_byte,
+            ..self.token_start
+        };
+
+        self.tokens.push(TokenWithSpan { token, span });
+    }
+
+    /// Records an error for the token being scanned, which is replaced
+    /// by an error token. Like `add_token`, this should be called while
+    /// on the token's last char.
+    fn add_error(&mut self, kind: ScanErrorKind, text: String) {
+        self.add_error_from(self.token_start, kind, text);
+        self.add_token(Token::Error);
+    }
+
+    // Records an error for source starting somewhere other than the token.
+    fn add_error_from(&mut self, start: Span, kind: ScanErrorKind, text: String) {
+        let end_byte =
+            self.current_position.byte_offset + self.current_char.map_or(0, char::len_utf8);
+        let span = Span { end_byte, ..start };
+
+        self.errors.push(ScanError { kind, span, text });
     }
 
+    // Moves each char of lookahead down a slot, reading a new third char.
     fn advance(&mut self) {
         self.current_char = self.next_char;
+        self.current_position = self.next_position;
         self.next_char = self.third_char;
-        self.third_char = self.reader.next();
+        self.next_position = self.third_position;
+
+        (self.third_char, self.third_position) = self.read_char();
     }
 
-    fn is_at_end(&self) -> bool {
-        self.current_char.is_none()
+    // Reads the next char from the source, with its position.
+    // Bytes that aren't utf-8 are reported and skipped. A read
+    // failure is reported and ends the source.
+    fn read_char(&mut self) -> (Option<char>, Position) {
+        loop {
+            match self.reader.next() {
+                Some((position, Ok(c))) => return (Some(c), position),
+                None => return (None, self.reader.position()),
+
+                Some((position, Err(error))) => self.add_decode_error(error, position),
+            }
+        }
     }
 
-    fn has_next(&self) -> bool {
-        self.next_char.is_some()
+    fn add_decode_error(&mut self, error: DecodeError, position: Position) {
+        let span = Span {
+            end_byte: position.byte_offset + error.byte_len(),
+            ..Span::at(position)
+        };
+
+        let (kind, text) = match error.kind {
+            DecodeErrorKind::InvalidSequence(bytes) => {
+                (ScanErrorKind::InvalidUtf8, escape_bytes(&bytes))
+            }
+            DecodeErrorKind::Io(error) => (ScanErrorKind::Io, error.to_string()),
+        };
+
+        self.errors.push(ScanError { kind, span, text });
+    }
+
+    // Empty span at the current char.
+    fn current_span(&self) -> Span {
+        Span::at(self.current_position)
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current_char.is_none()
     }
 
     // Currently used for line comments.
@@ -269,97 +489,328 @@ impl Scanner {
         }
     }
 
-    // We've seen a quote so try to read a string literal.
-    fn get_string_literal(&mut self) -> Option<String> {
-        // We are still on the starting '"'.
+    // A `///` comment is kept as a token, so the parser can attach it to
+    // the declaration after it. Four or more slashes make a plain comment,
+    // as in Rust.
+    fn scan_doc_comment(&mut self) {
+        self.advance();
+        self.advance();
+        if self.next_char == Some('/') {
+            self.consume_rest_of_line();
+            return;
+        }
+
+        let mut text = String::new();
+        while let Some(c) = self.next_char.filter(|c| *c != '\n') {
+            text.push(c);
+            self.advance();
+        }
+
+        let text = text
+            .strip_prefix(' ')
+            .unwrap_or(&text)
+            .trim_end_matches('\r');
+        self.add_token(Token::DocComment(text.to_owned()));
+    }
+
+    // Block comments nest, so commenting out code that already has
+    // one works. We stop on the closing '/', or at the end of the
+    // source if the comment isn't closed.
+    fn skip_block_comment(&mut self) {
+        self.advance();
+        let mut depth = 1;
+
+        while depth > 0 {
+            self.advance();
+            match (self.current_char, self.next_char) {
+                (None, _) => {
+                    self.add_error(ScanErrorKind::UnterminatedComment, String::from("/*"));
+                    return;
+                }
+                (Some('/'), Some('*')) => {
+                    self.advance();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance();
+                    depth -= 1;
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    // Scans a string literal, or the rest of one after an interpolated
+    // expression, in which case we're given the string's start.
+    fn scan_string(&mut self, interpolated_from: Option<Span>) {
+        let string_start = interpolated_from.unwrap_or(self.token_start);
+        let (text, end) = self.get_string_literal();
+
+        match end {
+            StringEnd::Interpolation => {
+                self.open_interpolations.push(OpenInterpolation {
+                    brace_depth: 0,
+                    string_start,
+                });
+
+                if interpolated_from.is_some() {
+                    self.add_token(Token::InterpolationMiddle(text));
+                } else {
+                    self.add_token(Token::InterpolationStart(text));
+                }
+            }
+
+            StringEnd::Quote => {
+                if interpolated_from.is_some() {
+                    self.add_token(Token::InterpolationEnd(text));
+                } else {
+                    self.add_token(Token::String(text));
+                }
+            }
+
+            StringEnd::Unterminated => {
+                self.add_error_from(
+                    string_start,
+                    ScanErrorKind::UnterminatedString,
+                    format!("\"{text}"),
+                );
+
+                // Still end the interpolation, so the
+                // parser doesn't report it too.
+                if interpolated_from.is_some() {
+                    self.add_token(Token::InterpolationEnd(text));
+                } else {
+                    self.add_token(Token::Error);
+                }
+            }
+        }
+    }
+
+    // We're on a string's opening quote, or the '}' ending an interpolated
+    // expression. Reads text up to the closing quote, where we stop, or up
+    // to a "${", where we stop on the '{'. Returns the text and which it was.
+    //
+    // Bad escape sequences are reported with their own spans, and left
+    // out of the string, so the rest of the literal is still scanned.
+    fn get_string_literal(&mut self) -> (String, StringEnd) {
         self.advance();
 
         let mut string = String::new();
         while self.current_char != Some('"') && !self.is_at_end() {
-            if self.next_char == Some('\n') {
-                self.current_line += 1;
+            if self.current_char == Some('$') && self.next_char == Some('{') {
+                self.advance();
+                return (string, StringEnd::Interpolation);
+            }
+
+            if self.current_char == Some('\\') {
+                let escape_start = self.current_span();
+                self.advance();
+                if self.is_at_end() {
+                    break;
+                }
+
+                match self.get_escape() {
+                    Ok(c) => string.push(c),
+
+                    Err((kind, text)) => self.add_error_from(escape_start, kind, text),
+                }
+            } else {
+                string.push(self.current_char.unwrap());
             }
 
-            string.push(self.current_char.unwrap());
             self.advance();
         }
 
-        // TODO: We can handle escaped quotes.
-
         if self.current_char == Some('"') {
-            Some(string)
+            (string, StringEnd::Quote)
         } else {
-            None
+            (string, StringEnd::Unterminated)
         }
     }
 
-    // NOTE: Currently we're not parsing the number string into an actual
-    // numeric value, we're just storing the literal for later parsing.
-    // This means we have to handle more validation of the literal format.
-    fn get_numeric_literal(&mut self) -> Option<String> {
-        // ------------------------------------
-        // Integral or floating point literals.
+    // We're on the char after a '\'. Like a token, this stops on the
+    // escape's last char. On error, we return the escape's text.
+    fn get_escape(&mut self) -> Result<char, (ScanErrorKind, String)> {
+        let c = self.current_char.unwrap();
+        match c {
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            // So "\${" isn't an interpolation.
+            '$' => Ok('$'),
 
-        let mut string = String::new();
+            // A code point in hex, like \u{1F600}.
+            'u' => {
+                let mut text = String::from("\\u");
+                if self.next_char != Some('{') {
+                    return Err((ScanErrorKind::InvalidUnicodeEscape, text));
+                }
+                text.push('{');
+                self.advance();
 
-        // Get consecutive digits.
-        string.push(self.current_char.unwrap());
-        while let Some(c) = self.next_char {
-            if !c.is_digit(10) {
-                break;
+                let mut digits = String::new();
+                while let Some(c) = self.next_char.filter(char::is_ascii_hexdigit) {
+                    digits.push(c);
+                    self.advance();
+                }
+                text.push_str(&digits);
+
+                if self.next_char != Some('}') {
+                    return Err((ScanErrorKind::InvalidUnicodeEscape, text));
+                }
+                text.push('}');
+                self.advance();
+
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err((ScanErrorKind::InvalidUnicodeEscape, text));
+                }
+
+                // Six hex digits always fit, but may not be a
+                // code point, like a surrogate or past 10FFFF.
+                let code_point = u32::from_str_radix(&digits, 16).unwrap();
+                char::from_u32(code_point).ok_or((ScanErrorKind::InvalidCodePoint, text))
+            }
+
+            c => Err((ScanErrorKind::UnknownEscape, format!("\\{c}"))),
+        }
+    }
+
+    // Reads a number literal: decimal, or hex, binary or octal after a
+    // 0x, 0b or 0o prefix. Digits may be separated by single '_'s. Only
+    // decimal literals can have a fraction or a 'd' suffix, making an
+    // exact decimal, or an exponent, making a float.
+    //
+    // For an invalid literal, we eat the rest of it, up to the next
+    // char that can't continue a number, and the error holds its text.
+    fn get_numeric_literal(&mut self) -> Result<NumberLiteral, (ScanErrorKind, String)> {
+        let mut text = String::from(self.current_char.unwrap());
+
+        let radix = match (self.current_char, self.next_char) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('b' | 'B')) => 2,
+            (Some('0'), Some('o' | 'O')) => 8,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            self.advance();
+            text.push(self.current_char.unwrap());
+
+            if !self.eat_digits(radix, &mut text) {
+                return Err(self.eat_invalid_literal(text));
+            }
+            if self.next_char.is_some_and(Self::continues_number) {
+                return Err(self.eat_invalid_literal(text));
             }
 
-            string.push(c);
+            // The digits are valid, so this can only fail by overflowing.
+            let digits = text[2..].replace('_', "");
+            return Ok(i64::from_str_radix(&digits, radix).map_or_else(
+                |_| NumberLiteral::BigInt(BigInt::from_digits(&digits, radix)),
+                NumberLiteral::Int,
+            ));
+        }
+
+        // We're on the first digit, so there may be no more.
+        self.eat_digits(10, &mut text);
+        let mut has_fraction = false;
+        let mut is_float = false;
+
+        // A '.' not followed by a digit isn't a fraction.
+        if self.next_char == Some('.') && self.third_char.is_some_and(|c| c.is_ascii_digit()) {
             self.advance();
+            text.push('.');
+            self.eat_digits(10, &mut text);
+            has_fraction = true;
         }
 
-        let is_valid_terminator =
-            |c: char| -> bool { c.is_ascii_whitespace() || c == ',' || c == ')' || c == ';' };
+        if self.next_char == Some('d') {
+            self.advance();
+            if self.next_char.is_some_and(Self::continues_number) {
+                text.push('d');
+                return Err(self.eat_invalid_literal(text));
+            }
 
-        // Check if this is an integral literal.
-        if !self.has_next() || is_valid_terminator(self.next_char.unwrap()) {
-            return Some(string);
+            return Ok(NumberLiteral::Decimal(Decimal::parse(
+                &text.replace('_', ""),
+            )));
         }
 
-        // ------------------------------------
-        // Specific to floating point literals.
+        if let Some(e @ ('e' | 'E')) = self.next_char {
+            self.advance();
+            text.push(e);
+            if let Some(sign @ ('+' | '-')) = self.next_char {
+                self.advance();
+                text.push(sign);
+            }
 
-        // To be valid number, next char must be '.'.
-        if self.next_char == Some('.') {
-            string.push('.');
-            self.advance()
-        } else {
-            return None;
+            if !self.eat_digits(10, &mut text) {
+                return Err(self.eat_invalid_literal(text));
+            }
+            is_float = true;
+        }
+
+        if self.next_char.is_some_and(Self::continues_number) {
+            return Err(self.eat_invalid_literal(text));
         }
 
-        // At least one digit must follow the '.'.
-        if !self.has_next() || !self.next_char.unwrap().is_digit(10) {
-            // TODO: Consider eating chars until next valid terminator.
-            return None;
+        let digits = text.replace('_', "");
+        if is_float {
+            match digits.parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(NumberLiteral::Float(number)),
+                _ => Err((ScanErrorKind::NumberOverflow, text)),
+            }
+        } else if has_fraction {
+            Ok(NumberLiteral::Decimal(Decimal::parse(&digits)))
+        } else {
+            // The digits are valid, so this can only fail by overflowing.
+            Ok(digits.parse::<i64>().map_or_else(
+                |_| NumberLiteral::BigInt(BigInt::from_digits(&digits, 10)),
+                NumberLiteral::Int,
+            ))
         }
+    }
 
-        // Get consecutive digits.
-        while let Some(c) = self.next_char {
-            if !c.is_digit(10) {
-                break;
+    // Appends any digits in the radix that come next onto the text of a
+    // number, with '_'s between them. Returns whether there were any.
+    fn eat_digits(&mut self, radix: u32, text: &mut String) -> bool {
+        let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_digit(radix));
+
+        let mut found_digit = false;
+        loop {
+            if is_digit(self.next_char) {
+                found_digit = true;
+            } else if !(self.next_char == Some('_')
+                && text.ends_with(|c: char| c.is_digit(radix))
+                && is_digit(self.third_char))
+            {
+                return found_digit;
             }
 
-            string.push(c);
             self.advance();
+            text.push(self.current_char.unwrap());
         }
+    }
 
-        match self.next_char {
-            Some(c) => {
-                if is_valid_terminator(c) {
-                    Some(string)
-                } else {
-                    // TODO: Consider eating chars until next valid terminator.
-                    None
-                }
-            }
+    // Chars that can't directly follow a number, since
+    // they'd make it look like part of the literal.
+    fn continues_number(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_' || c == '.'
+    }
 
-            None => Some(string),
+    // Appends the rest of an invalid literal onto its text,
+    // so we can report all of it.
+    fn eat_invalid_literal(&mut self, mut text: String) -> (ScanErrorKind, String) {
+        while let Some(c) = self.next_char.filter(|c| Self::continues_number(*c)) {
+            text.push(c);
+            self.advance();
         }
+
+        (ScanErrorKind::InvalidNumber, text)
     }
 
     fn get_identifier(&mut self) -> String {
@@ -377,6 +828,343 @@ impl Scanner {
     // Recognizes chars valid for characters after the
     // first in a user identifier or reserved word.
     fn is_identifier_char(c: &char) -> bool {
-        c.is_ascii_alphabetic() || *c == '_'
+        c.is_ascii_alphanumeric() || *c == '_'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    fn span(line: usize, column: usize, start_byte: usize, end_byte: usize) -> Span {
+        Span {
+            line,
+            column,
+            utf16_column: column,
+            start_byte,
+            end_byte,
+        }
+    }
+
+    fn scan(source: &str) -> Scanner {
+        let mut scanner = Scanner::from_str(source);
+        scanner.scan_tokens();
+        scanner
+    }
+
+    fn tokens(scanner: &Scanner) -> Vec<Token> {
+        scanner
+            .tokens
+            .iter()
+            .map(|token| token.token.clone())
+            .collect()
+    }
+
+    #[test]
+    fn reports_the_errors_in_scanner_test_program() {
+        let scanner = scan(include_str!("../../../programs/scanner_test.iris"));
+
+        let expected = [
+            (ScanErrorKind::InvalidNumber, span(2, 9, 65, 67), "5."),
+            (ScanErrorKind::InvalidNumber, span(3, 9, 77, 82), "42..0"),
+            (
+                ScanErrorKind::UnterminatedString,
+                span(4, 9, 92, 107),
+                "\"unterminated;\n",
+            ),
+        ];
+        assert_eq!(scanner.errors.len(), expected.len());
+        for (error, (kind, span, text)) in scanner.errors.iter().zip(expected) {
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.span, span);
+            assert_eq!(error.text, text);
+        }
+    }
+
+    #[test]
+    fn replaces_invalid_literals_with_error_tokens() {
+        let scanner = scan("print 5.;");
+
+        let tokens: Vec<_> = scanner.tokens.iter().map(|token| &token.token).collect();
+        assert_eq!(
+            tokens,
+            [&Token::Print, &Token::Error, &Token::Semicolon, &Token::EOF]
+        );
+        assert_eq!(scanner.tokens[1].span, scanner.errors[0].span);
+    }
+
+    #[test]
+    fn carries_on_after_an_error() {
+        let scanner = scan("42..0 + 7");
+
+        assert_eq!(scanner.errors.len(), 1);
+        assert_eq!(
+            scanner.tokens[2].token,
+            Token::Number(NumberLiteral::Int(7))
+        );
+        assert_eq!(scanner.tokens[2].span, span(1, 9, 8, 9));
+    }
+
+    #[test]
+    fn counts_the_line_ending_a_comment_once() {
+        let scanner = scan("// one\nprint // two\r\n  x;");
+
+        assert_eq!(scanner.tokens[0].span, span(2, 1, 7, 12));
+        assert_eq!(scanner.tokens[1].span, span(3, 3, 23, 24));
+    }
+
+    #[test]
+    fn scans_strings_and_readers_the_same() {
+        // Multi-byte chars, tabs and CRLFs all move columns and offsets.
+        const SOURCE: &str = "var pi = 3.14; // π\r\n\tprint \"née ${pi * 2} 😀\";\n\"€";
+
+        let mut from_str = Scanner::from_str(SOURCE);
+        from_str.scan_tokens();
+        let mut from_reader = Scanner::from_reader(Cursor::new(SOURCE.as_bytes()));
+        from_reader.scan_tokens();
+
+        assert_eq!(from_str.tokens, from_reader.tokens);
+        assert_eq!(from_str.errors, from_reader.errors);
+        assert_eq!(from_str.errors.len(), 1);
+    }
+
+    #[test]
+    fn ends_an_interpolation_at_its_own_brace() {
+        // The '}' in the inner string doesn't end the interpolation.
+        let scanner = scan(r#""${ "}" }""#);
+
+        assert!(scanner.errors.is_empty());
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::InterpolationStart(String::new()),
+                Token::String(String::from("}")),
+                Token::InterpolationEnd(String::new()),
+                Token::EOF,
+            ]
+        );
+        assert_eq!(scanner.tokens[0].span, span(1, 1, 0, 3));
+        assert_eq!(scanner.tokens[1].span, span(1, 5, 4, 7));
+        assert_eq!(scanner.tokens[2].span, span(1, 9, 8, 10));
+    }
+
+    #[test]
+    fn nests_interpolated_strings() {
+        let scanner = scan(r#""a ${"b ${1} c"} d""#);
+
+        assert!(scanner.errors.is_empty());
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::InterpolationStart(String::from("a ")),
+                Token::InterpolationStart(String::from("b ")),
+                Token::Number(NumberLiteral::Int(1)),
+                Token::InterpolationEnd(String::from(" c")),
+                Token::InterpolationEnd(String::from(" d")),
+                Token::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_an_interpolation_that_never_ends() {
+        let scanner = scan(r#""a ${ 1"#);
+
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::InterpolationStart(String::from("a ")),
+                Token::Number(NumberLiteral::Int(1)),
+                Token::Error,
+                Token::EOF,
+            ]
+        );
+        assert_eq!(scanner.errors.len(), 1);
+        assert_eq!(scanner.errors[0].kind, ScanErrorKind::UnterminatedString);
+        assert_eq!(scanner.errors[0].span, span(1, 1, 0, 7));
+    }
+
+    #[test]
+    fn scans_an_empty_interpolation() {
+        // The parser reports the missing expression.
+        let scanner = scan(r#""${}""#);
+
+        assert!(scanner.errors.is_empty());
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::InterpolationStart(String::new()),
+                Token::InterpolationEnd(String::new()),
+                Token::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_each_form_of_number() {
+        let big = BigInt::from_digits("9223372036854775808", 10);
+        let cases = [
+            ("0x1F", NumberLiteral::Int(31)),
+            ("0b1010", NumberLiteral::Int(10)),
+            ("0o17", NumberLiteral::Int(15)),
+            ("1_000", NumberLiteral::Int(1000)),
+            ("6.02E23", NumberLiteral::Float(6.02e23)),
+            ("1e-9", NumberLiteral::Float(1e-9)),
+            // A fraction without an exponent is exact.
+            ("0.1", NumberLiteral::Decimal(Decimal::parse("0.1"))),
+            (
+                "1_000.50",
+                NumberLiteral::Decimal(Decimal::parse("1000.50")),
+            ),
+            ("7d", NumberLiteral::Decimal(Decimal::parse("7"))),
+            ("9223372036854775807", NumberLiteral::Int(i64::MAX)),
+            // Integers too big for an i64 are big ints.
+            ("9223372036854775808", NumberLiteral::BigInt(big.clone())),
+            ("0x8000_0000_0000_0000", NumberLiteral::BigInt(big)),
+        ];
+
+        for (source, number) in cases {
+            let scanner = scan(source);
+            assert!(scanner.errors.is_empty(), "{source}");
+            assert_eq!(tokens(&scanner), [Token::Number(number), Token::EOF]);
+        }
+    }
+
+    #[test]
+    fn reports_invalid_numbers_whole() {
+        let cases = [
+            ("0x", ScanErrorKind::InvalidNumber),
+            ("1__0", ScanErrorKind::InvalidNumber),
+            ("1_", ScanErrorKind::InvalidNumber),
+            ("0x_1", ScanErrorKind::InvalidNumber),
+            ("1e", ScanErrorKind::InvalidNumber),
+            ("12abc", ScanErrorKind::InvalidNumber),
+            ("1e400", ScanErrorKind::NumberOverflow),
+        ];
+
+        for (literal, kind) in cases {
+            let scanner = scan(&format!("print {literal};"));
+
+            assert_eq!(scanner.errors.len(), 1, "{literal}");
+            let error = &scanner.errors[0];
+            assert_eq!(error.kind, kind, "{literal}");
+            assert_eq!(error.span, span(1, 7, 6, 6 + literal.len()), "{literal}");
+            assert_eq!(error.text, literal);
+            // Scanning carries on after the literal.
+            assert_eq!(
+                tokens(&scanner),
+                [Token::Print, Token::Error, Token::Semicolon, Token::EOF]
+            );
+        }
+    }
+
+    #[test]
+    fn scans_escape_sequences() {
+        let cases = [
+            (r#""\" \\ \n \t \r \0""#, "\" \\ \n \t \r \0"),
+            // An escaped '$' doesn't start an interpolation.
+            (r#""\${x}""#, "${x}"),
+            (r#""\u{e9} \u{1F600}""#, "é 😀"),
+        ];
+
+        for (source, string) in cases {
+            let scanner = scan(source);
+            assert!(scanner.errors.is_empty(), "{source}");
+            assert_eq!(
+                tokens(&scanner),
+                [Token::String(String::from(string)), Token::EOF]
+            );
+        }
+    }
+
+    #[test]
+    fn reports_invalid_escapes_and_keeps_the_string() {
+        let cases = [
+            (r"\q", ScanErrorKind::UnknownEscape, r"\q"),
+            // Only the "\u" is wrong here; the digits are kept.
+            (r"\u12", ScanErrorKind::InvalidUnicodeEscape, r"\u"),
+            (r"\u{D800}", ScanErrorKind::InvalidCodePoint, r"\u{D800}"),
+            (
+                r"\u{110000}",
+                ScanErrorKind::InvalidCodePoint,
+                r"\u{110000}",
+            ),
+            (
+                r"\u{1234567}",
+                ScanErrorKind::InvalidUnicodeEscape,
+                r"\u{1234567}",
+            ),
+        ];
+
+        for (escape, kind, text) in cases {
+            let scanner = scan(&format!("\"a{escape}b\""));
+
+            assert_eq!(scanner.errors.len(), 1, "{escape}");
+            let error = &scanner.errors[0];
+            assert_eq!(error.kind, kind, "{escape}");
+            assert_eq!(error.span, span(1, 3, 2, 2 + text.len()), "{escape}");
+            assert_eq!(error.text, text);
+
+            let rest = &escape[text.len()..];
+            assert_eq!(
+                tokens(&scanner),
+                [Token::String(format!("a{rest}b")), Token::EOF]
+            );
+        }
+    }
+
+    #[test]
+    fn skips_nested_block_comments() {
+        let scanner = scan("1 /* a /* b */ c */ 2");
+
+        assert!(scanner.errors.is_empty());
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::Number(NumberLiteral::Int(1)),
+                Token::Number(NumberLiteral::Int(2)),
+                Token::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_block_comments() {
+        // The inner comment closes, but the outer one doesn't.
+        // And in "/*/", the '*' can't also start the "*/".
+        for source in ["1 /* a /* b */ c", "1 /*/ 2"] {
+            let scanner = scan(source);
+
+            assert_eq!(scanner.errors.len(), 1, "{source}");
+            let error = &scanner.errors[0];
+            assert_eq!(error.kind, ScanErrorKind::UnterminatedComment);
+            assert_eq!(error.span, span(1, 3, 2, source.len()), "{source}");
+            assert_eq!(
+                tokens(&scanner),
+                [
+                    Token::Number(NumberLiteral::Int(1)),
+                    Token::Error,
+                    Token::EOF,
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn scans_doc_comments_line_by_line() {
+        let scanner = scan("/// One.\r\n///Two.\n//// Not a doc.\nfun");
+
+        assert_eq!(
+            tokens(&scanner),
+            [
+                Token::DocComment(String::from("One.")),
+                Token::DocComment(String::from("Two.")),
+                Token::Fun,
+                Token::EOF,
+            ]
+        );
+        assert_eq!(scanner.tokens[1].span, span(2, 1, 10, 17));
     }
 }
@@ -0,0 +1,173 @@
+/// Tracks where each char of the source is, so the
+/// scanner doesn't have to count lines and columns.
+///
+///
+use crate::parser::DecodeError;
+
+/// Where a char starts in the source. Line and columns are 1-based,
+/// and the byte offset is 0-based. We count columns both in chars and
+/// in UTF-16 code units, which is what many editors expect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub utf16_column: usize,
+    pub byte_offset: usize,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position {
+            line: 1,
+            column: 1,
+            utf16_column: 1,
+            byte_offset: 0,
+        }
+    }
+}
+
+/// Wraps an iterator of decoded chars, yielding each with its
+/// position. Bytes that failed to decode still take up space in
+/// the source, so an error moves the position on by one column.
+pub struct SourceCursor<I> {
+    chars: I,
+    position: Position,
+}
+
+impl<I: Iterator<Item = Result<char, DecodeError>>> SourceCursor<I> {
+    pub fn new(chars: I) -> SourceCursor<I> {
+        SourceCursor {
+            chars,
+            position: Position::default(),
+        }
+    }
+
+    /// Position of the next char, which is
+    /// the end of the source once we're done.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl<I: Iterator<Item = Result<char, DecodeError>>> Iterator for SourceCursor<I> {
+    type Item = (Position, Result<char, DecodeError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.position;
+        let result = self.chars.next()?;
+
+        match &result {
+            Ok('\n') => {
+                self.position.line += 1;
+                self.position.column = 1;
+                self.position.utf16_column = 1;
+                self.position.byte_offset += 1;
+            }
+            Ok(c) => {
+                self.position.column += 1;
+                self.position.utf16_column += c.len_utf16();
+                self.position.byte_offset += c.len_utf8();
+            }
+
+            Err(error) => {
+                self.position.column += 1;
+                self.position.utf16_column += 1;
+                self.position.byte_offset += error.byte_len();
+            }
+        }
+
+        Some((position, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parser::DecodeErrorKind;
+
+    fn position(line: usize, column: usize, utf16_column: usize, byte_offset: usize) -> Position {
+        Position {
+            line,
+            column,
+            utf16_column,
+            byte_offset,
+        }
+    }
+
+    // Positions of each char in the source, then the end position.
+    fn positions(source: &str) -> (Vec<(char, Position)>, Position) {
+        let mut cursor = SourceCursor::new(source.chars().map(Ok));
+        let chars = cursor
+            .by_ref()
+            .map(|(position, result)| (result.unwrap(), position))
+            .collect();
+
+        (chars, cursor.position())
+    }
+
+    #[test]
+    fn counts_a_commented_line_once() {
+        let (chars, end) = positions("// note\nx");
+
+        assert_eq!(chars[7], ('\n', position(1, 8, 8, 7)));
+        assert_eq!(chars[8], ('x', position(2, 1, 1, 8)));
+        assert_eq!(end, position(2, 2, 2, 9));
+    }
+
+    #[test]
+    fn starts_a_line_after_crlf() {
+        let (chars, end) = positions("a\r\nb");
+
+        assert_eq!(
+            chars,
+            [
+                ('a', position(1, 1, 1, 0)),
+                ('\r', position(1, 2, 2, 1)),
+                ('\n', position(1, 3, 3, 2)),
+                ('b', position(2, 1, 1, 3)),
+            ]
+        );
+        assert_eq!(end, position(2, 2, 2, 4));
+    }
+
+    #[test]
+    fn counts_a_tab_as_one_column() {
+        let (chars, _) = positions("\t\tx");
+
+        assert_eq!(chars[2], ('x', position(1, 3, 3, 2)));
+    }
+
+    #[test]
+    fn counts_chars_and_utf16_units_separately() {
+        // é is one UTF-16 unit and two bytes; 😀 is two units and four bytes.
+        let (chars, end) = positions("é😀x");
+
+        assert_eq!(
+            chars,
+            [
+                ('é', position(1, 1, 1, 0)),
+                ('😀', position(1, 2, 2, 2)),
+                ('x', position(1, 3, 4, 6)),
+            ]
+        );
+        assert_eq!(end, position(1, 4, 5, 7));
+    }
+
+    #[test]
+    fn moves_one_column_past_a_decode_error() {
+        let invalid = DecodeError {
+            kind: DecodeErrorKind::InvalidSequence(vec![0xE2, 0x82]),
+            byte_offset: 1,
+        };
+        let mut cursor = SourceCursor::new([Ok('a'), Err(invalid), Ok('b')].into_iter());
+
+        assert_eq!(cursor.next().unwrap().0, position(1, 1, 1, 0));
+        let (error_position, result) = cursor.next().unwrap();
+        assert_eq!(error_position, position(1, 2, 2, 1));
+        assert!(result.is_err());
+        assert_eq!(cursor.next().unwrap().0, position(1, 3, 3, 3));
+        assert!(cursor.next().is_none());
+        assert_eq!(cursor.position(), position(1, 4, 4, 4));
+    }
+}
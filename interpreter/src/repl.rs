@@ -0,0 +1,124 @@
+/// Interactive prompt for the interpreter.
+///
+/// Each chunk of input is scanned, parsed, resolved and run against
+/// one long-lived interpreter, so definitions persist from one line
+/// to the next. Errors are reported and the prompt carries on.
+///
+use crate::diagnostics::{Diagnostic, SourceMap};
+use crate::interpreter::{Interpreter, Resolver, Value};
+use crate::parser::grammar::Stmt;
+use crate::parser::scanner::{Scanner, Token};
+use crate::parser::{Parser, ScanErrorKind};
+
+use std::io::{self, BufRead, Write};
+use std::slice;
+
+pub fn run_prompt(script_args: Vec<String>) -> io::Result<()> {
+    let mut interpreter = Interpreter::with_script_args(script_args);
+    let mut lines = io::stdin().lock().lines();
+
+    // Input read so far for the current chunk.
+    let mut source = String::new();
+
+    loop {
+        print!("{}", if source.is_empty() { "> " } else { "... " });
+        io::stdout().flush()?;
+
+        let Some(line) = lines.next() else {
+            break;
+        };
+        source.push_str(&line?);
+        source.push('\n');
+
+        let mut scanner = Scanner::from_str(&source);
+        scanner.scan_tokens();
+
+        // Keep reading lines until the chunk looks complete.
+        if is_incomplete(&scanner) {
+            continue;
+        }
+
+        run_chunk(&mut interpreter, scanner, &source);
+        source.clear();
+    }
+
+    // Input ended partway through a chunk, so report what's wrong with it.
+    if !source.is_empty() {
+        let mut scanner = Scanner::from_str(&source);
+        scanner.scan_tokens();
+        run_chunk(&mut interpreter, scanner, &source);
+    }
+
+    println!();
+    Ok(())
+}
+
+// A chunk is incomplete if it has unclosed braces or parens,
+// or it ends inside a string literal.
+fn is_incomplete(scanner: &Scanner) -> bool {
+    let mut depth: isize = 0;
+    for token in &scanner.tokens {
+        match token.token {
+            Token::LeftBrace | Token::LeftParen => depth += 1,
+            Token::RightBrace | Token::RightParen => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let in_string_or_comment = scanner.errors.iter().any(|error| {
+        matches!(
+            error.kind,
+            ScanErrorKind::UnterminatedString | ScanErrorKind::UnterminatedComment
+        )
+    });
+
+    depth > 0 || in_string_or_comment
+}
+
+fn run_chunk(interpreter: &mut Interpreter, scanner: Scanner, source: &str) {
+    let source_map = SourceMap::new("<repl>", source.as_bytes().to_vec());
+    let report = |diagnostic: Diagnostic| eprint!("{}", diagnostic.render(&source_map));
+
+    for error in &scanner.errors {
+        report(error.into());
+    }
+    let had_scan_error = !scanner.errors.is_empty();
+
+    let statements = match Parser::new(scanner.tokens).parse_program() {
+        Ok(_) if had_scan_error => return,
+        Ok(statements) => statements,
+
+        Err(errors) => {
+            for error in &errors {
+                report(error.into());
+            }
+            return;
+        }
+    };
+
+    if let Err(errors) = Resolver::new().resolve(&statements) {
+        for error in &errors {
+            report(error.into());
+        }
+        return;
+    }
+
+    for statement in &statements {
+        let result = match statement {
+            // Echo the value of a bare expression, except nil,
+            // so calls made for their side effects stay quiet.
+            Stmt::Expression(expression) => interpreter.evaluate(expression).map(|value| {
+                if value != Value::Nil {
+                    println!("{value}");
+                }
+            }),
+
+            _ => interpreter.interpret(slice::from_ref(statement)),
+        };
+
+        if let Err(error) = result {
+            report((&error).into());
+            return;
+        }
+    }
+}